@@ -9,6 +9,7 @@ import { spawn, execSync, ChildProcess } from 'child_process';
 import { existsSync, createWriteStream, mkdirSync } from 'fs';
 import { createProxyMiddleware } from 'http-proxy-middleware';
 import { setupAuth, authMiddleware, AUTH_COOKIE_NAME } from './auth.js';
+import { isTunnelUpgrade, recordTunnelActivity, tunnelAccessMiddleware, tunnelActivity, tunnelGateEnabled, upgradeAllowed } from './tunnel-access.js';
 import { PtyManager } from './pty-manager.js';
 import { setupFileApi } from './file-api.js';
 import { setupPortProxy, setupWebSocketProxy } from './port-proxy.js';
@@ -127,6 +128,11 @@ console.log('NODE_PTY_BINARY:', process.env.NODE_PTY_BINARY);
 console.log('SHELL:', process.env.SHELL);
 console.log('PATH (first 100 chars):', process.env.PATH?.substring(0, 100));
 const PORT = parseInt(process.env.PORT || '3456', 10);
+// Loopback unless the desktop app runs in LAN mode, which sets 0.0.0.0 so
+// phones on the same network can connect over IPv4 (gated by the access
+// token; the app only hands out and advertises IPv4 addresses). Set it
+// by hand to reach a server started for external server mode from elsewhere.
+const BIND_HOST = process.env.MT_BIND_HOST || '127.0.0.1';
 
 // Force-kill any process using our port to prevent startup conflicts
 // This ensures the app ALWAYS starts successfully
@@ -203,10 +209,14 @@ app.use(cookieParser());
 
 // Health check endpoint - used by Tauri to verify server is ready
 // No auth required, responds immediately
+// tunnelGate tells the app whether tunnel visitors need the access token
 app.get('/health', (req, res) => {
-  res.json({ status: 'ok', timestamp: Date.now() });
+  res.json({ status: 'ok', timestamp: Date.now(), tunnelGate: tunnelGateEnabled() });
 });
 
+// Tunnel visitors need the per-session token minted by the desktop app
+app.use(tunnelAccessMiddleware(isTunnelRequest));
+
 // CORS for REST API - handle development, production, and tunnel modes
 app.use((req, res, next) => {
   const origin = req.headers.origin;
@@ -279,6 +289,17 @@ const ptyManager = new PtyManager();
   }
 })();
 
+// Session and tunnel activity summary - used by Tauri to install updates only
+// when terminals are idle and to stop an unused tunnel
+// Local requests only, no auth required
+app.get('/api/activity', (req, res) => {
+  if (isTunnelRequest(req)) {
+    res.status(403).json({ error: 'Forbidden' });
+    return;
+  }
+  res.json({ ...ptyManager.getActivitySummary(), ...tunnelActivity(), now: Date.now() });
+});
+
 // Track which sessions have broadcast handlers set up (to prevent double broadcasts)
 const sessionBroadcastHandlers = new Set<string>();
 
@@ -1160,8 +1181,12 @@ app.use('/api', (req, res, next) => {
   next();
 });
 
-// Socket.io authentication - disabled for simplified desktop app usage
+// Socket.io authentication - local clients are trusted; tunnel clients need
+// the cookie set when they opened the tokenized link
 io.use((socket, next) => {
+  if (!upgradeAllowed(socket.request)) {
+    return next(new Error('Tunnel access token required'));
+  }
   return next();
 });
 
@@ -1169,6 +1194,15 @@ io.use((socket, next) => {
 io.on('connection', (socket) => {
   console.log('Client connected:', socket.id);
 
+  // Terminal traffic from a remote client keeps the tunnel from idling out
+  if (isTunnelUpgrade(socket.request)) {
+    recordTunnelActivity();
+    socket.use((_packet, next) => {
+      recordTunnelActivity();
+      next();
+    });
+  }
+
   // Track legacy session for backward compatibility
   let legacySessionId: string | null = null;
 
@@ -1800,8 +1834,8 @@ function stopClaudeMem(): void {
   }
 }
 
-server.listen(PORT, '127.0.0.1', () => {
-  console.log(`Mobile Terminal server running on http://127.0.0.1:${PORT}`);
+server.listen(PORT, BIND_HOST, () => {
+  console.log(`Mobile Terminal server running on http://${BIND_HOST}:${PORT}`);
   if (!process.env.AUTH_TOKEN) {
     console.log('WARNING: No AUTH_TOKEN set. Using default password.');
   }
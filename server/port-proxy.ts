@@ -1,5 +1,6 @@
 import { Express, Request, Response } from 'express';
 import httpProxy from 'http-proxy';
+import { upgradeAllowed } from './tunnel-access.js';
 
 const proxy = httpProxy.createProxyServer({
   changeOrigin: true,
@@ -179,6 +180,12 @@ export function setupWebSocketProxy(server: import('http').Server): void {
   server.on('upgrade', (req, socket, head) => {
     const url = req.url || '';
 
+    // Socket.io checks its own handshake; everything else is refused here
+    if (!url.startsWith('/socket.io') && !upgradeAllowed(req)) {
+      socket.destroy();
+      return;
+    }
+
     // Handle memory WebSocket
     if (url.startsWith('/memory')) {
       const target = 'http://127.0.0.1:37777';
@@ -767,6 +767,19 @@ export class PtyManager {
     return null;
   }
 
+  /**
+   * Summarize session activity (used by the desktop app to defer updates until idle)
+   */
+  getActivitySummary(): { sessions: number; lastActivity: number | null } {
+    let lastActivity: number | null = null;
+    for (const session of this.sessions.values()) {
+      if (lastActivity === null || session.lastActivity > lastActivity) {
+        lastActivity = session.lastActivity;
+      }
+    }
+    return { sessions: this.sessions.size, lastActivity };
+  }
+
   /**
    * Write data directly to a session's PTY
    */
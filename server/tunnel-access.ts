@@ -0,0 +1,187 @@
+import { Request, Response, NextFunction } from 'express';
+import type { IncomingMessage } from 'http';
+import { readFileSync, statSync } from 'fs';
+import crypto from 'crypto';
+
+export const TUNNEL_COOKIE_NAME = 'mt_tunnel';
+
+// Written by the desktop app with a fresh token each time it starts a tunnel,
+// and kept while LAN mode is on.
+// Unset for web-only runs, which leaves the gate off.
+const TOKEN_FILE = process.env.MT_TUNNEL_TOKEN_FILE;
+
+const COOKIE_MAX_AGE = 30 * 24 * 60 * 60 * 1000;
+
+// Last accepted request through the tunnel; the desktop app reads it from
+// /api/activity to stop a tunnel nobody is using
+let lastTunnelRequest: number | null = null;
+
+let cachedToken: string | null = null;
+let cachedMtime = 0;
+
+// Re-read only when the file changes; a missing file means no tunnel is up
+function currentToken(): string | null {
+  if (!TOKEN_FILE) return null;
+  try {
+    const mtime = statSync(TOKEN_FILE).mtimeMs;
+    if (mtime !== cachedMtime) {
+      cachedToken = readFileSync(TOKEN_FILE, 'utf-8').trim() || null;
+      cachedMtime = mtime;
+    }
+    return cachedToken;
+  } catch {
+    cachedToken = null;
+    cachedMtime = 0;
+    return null;
+  }
+}
+
+function safeEqual(a: string, b: string): boolean {
+  const left = Buffer.from(a);
+  const right = Buffer.from(b);
+  return left.length === right.length && crypto.timingSafeEqual(left, right);
+}
+
+// The cookie holds a digest so the token itself is not replayed on every request
+function cookieValue(token: string): string {
+  return crypto.createHash('sha256').update(`mt-tunnel:${token}`).digest('hex');
+}
+
+export function recordTunnelActivity(): void {
+  lastTunnelRequest = Date.now();
+}
+
+export function tunnelActivity(): { lastTunnelRequest: number | null } {
+  return { lastTunnelRequest };
+}
+
+export function tunnelGateEnabled(): boolean {
+  return Boolean(TOKEN_FILE);
+}
+
+// A cookie from a previous tunnel session stops working once the token rotates
+export function hasTunnelAccess(cookie: string | undefined): boolean {
+  const token = currentToken();
+  return Boolean(token && cookie && safeEqual(cookie, cookieValue(token)));
+}
+
+export function parseCookie(header: string | undefined, name: string): string | undefined {
+  if (!header) return undefined;
+  for (const part of header.split(';')) {
+    const index = part.indexOf('=');
+    if (index > 0 && part.slice(0, index).trim() === name) {
+      return decodeURIComponent(part.slice(index + 1).trim());
+    }
+  }
+  return undefined;
+}
+
+// Same check as isTunnelRequest in index.ts, for raw upgrade requests
+export function isTunnelUpgrade(req: IncomingMessage): boolean {
+  const host = req.headers.host || '';
+  return host.includes('trycloudflare.com') ||
+         req.headers['cf-ray'] !== undefined ||
+         req.headers['cf-visitor'] !== undefined;
+}
+
+// cloudflared connects from loopback too, so tunnel visitors are told apart by
+// their headers; anything else off this machine is a LAN client
+function isLoopback(address: string | undefined): boolean {
+  return !address ||
+         address === '::1' ||
+         address.startsWith('127.') ||
+         address.startsWith('::ffff:127.');
+}
+
+// WebSocket upgrades carry the cookie set by the first page load
+export function upgradeAllowed(req: IncomingMessage): boolean {
+  if (!tunnelGateEnabled()) return true;
+  if (!isTunnelUpgrade(req) && isLoopback(req.socket.remoteAddress)) return true;
+  return hasTunnelAccess(parseCookie(req.headers.cookie, TUNNEL_COOKIE_NAME));
+}
+
+// Requests arriving through the tunnel, or from other machines when the server
+// listens on the LAN, must present the session token once via ?token=, which is
+// swapped for a cookie. Requests from this machine are never gated.
+export function tunnelAccessMiddleware(isTunnelRequest: (req: Request) => boolean) {
+  return (req: Request, res: Response, next: NextFunction): void => {
+    const tunnel = isTunnelRequest(req);
+    if (!tunnel && isLoopback(req.socket.remoteAddress)) {
+      return next();
+    }
+    // Only tunnel traffic keeps the tunnel from idling out
+    const recordActivity = () => {
+      if (tunnel) recordTunnelActivity();
+    };
+    if (!tunnelGateEnabled()) {
+      recordActivity();
+      return next();
+    }
+
+    const token = currentToken();
+    if (!token) {
+      res.status(503).json({ error: tunnel ? 'Tunnel is not active' : 'Remote access is not active' });
+      return;
+    }
+
+    if (hasTunnelAccess(req.cookies?.[TUNNEL_COOKIE_NAME])) {
+      recordActivity();
+      return next();
+    }
+
+    const presented = typeof req.query.token === 'string' ? req.query.token : undefined;
+    if (presented && safeEqual(presented, token)) {
+      recordActivity();
+      res.cookie(TUNNEL_COOKIE_NAME, cookieValue(token), {
+        httpOnly: true,
+        // LAN clients use plain http; the tunnel is always https
+        secure: tunnel,
+        sameSite: 'lax',
+        maxAge: COOKIE_MAX_AGE,
+      });
+
+      // Drop the token from the address bar and browser history
+      if (req.method === 'GET') {
+        const url = new URL(req.originalUrl, 'http://localhost');
+        url.searchParams.delete('token');
+        res.redirect(url.pathname + url.search);
+        return;
+      }
+      return next();
+    }
+
+    console.log(`[TunnelAccess] Rejected ${req.method} ${req.path}`);
+    if (req.path.startsWith('/api')) {
+      res.status(401).json({ error: 'Tunnel access token required' });
+      return;
+    }
+    res.status(401).send(`
+      <!DOCTYPE html>
+      <html lang="en">
+      <head>
+        <meta charset="UTF-8">
+        <meta name="viewport" content="width=device-width, initial-scale=1.0">
+        <title>Terminal Tunnel</title>
+        <style>
+          body {
+            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+            background: #1a1a2e;
+            color: rgba(255, 255, 255, 0.8);
+            min-height: 100vh;
+            margin: 0;
+            display: flex;
+            align-items: center;
+            justify-content: center;
+            padding: 20px;
+            text-align: center;
+          }
+        </style>
+      </head>
+      <body>
+        <p>This link is missing its access token or has expired.<br>
+        Open the full link or QR code from the Terminal Tunnel desktop app.</p>
+      </body>
+      </html>
+    `);
+  };
+}
@@ -0,0 +1,399 @@
+// The desktop app: Tauri setup, window and webview wiring, and the commands
+// that need an AppHandle. Everything here is behind the `gui` feature so the
+// daemon and CLI build without GTK/WebKit.
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use tauri::ipc::CapabilityBuilder;
+use tauri::{AppHandle, Emitter, Listener, Manager, Runtime};
+use tauri_plugin_dialog::{Dialog, FileDialogBuilder};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+
+#[cfg(unix)]
+use crate::control;
+use crate::{
+    autostart, copy_share_url, default_log_dir, diagnostics, lan, logs, monitor, notifications,
+    qr, redact, secrets, server_url, settings, share_url, start_server_internal, start_services,
+    start_tunnel_internal, stop_server_internal, stop_tunnel_internal, tray, updates, AppState,
+    EventHandler, Host,
+};
+
+#[tauri::command]
+async fn request_folder_access<R: Runtime>(
+    app: AppHandle<R>,
+    _path: String,
+) -> Result<Option<String>, String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let dialog_plugin_state = app.state::<Dialog<R>>();
+    FileDialogBuilder::new(dialog_plugin_state.inner().clone())
+        .pick_folder(move |path| {
+            tx.send(path).unwrap();
+        });
+
+    // This will block until the user has selected a folder or cancelled the dialog
+    match rx.recv().unwrap() {
+        Some(path) => {
+            let path_str = path.to_string();
+            log::info!("User granted access to folder: {}", path_str);
+            Ok(Some(path_str))
+        }
+        None => {
+            log::info!("User cancelled folder picker");
+            Ok(None)
+        }
+    }
+}
+
+impl Host for AppHandle {
+    fn resource_dir(&self) -> Option<PathBuf> {
+        self.path().resource_dir().ok()
+    }
+
+    fn publish(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.emit(event, payload);
+    }
+
+    fn clone_host(&self) -> Arc<dyn Host> {
+        Arc::new(self.clone())
+    }
+
+    fn app_state(&self) -> &AppState {
+        self.state::<AppState>().inner()
+    }
+
+    fn shutdown(&self) {
+        // The exit handler stops child processes
+        self.exit(0);
+    }
+
+    fn subscribe(&self, event: &str, handler: EventHandler) -> u32 {
+        self.listen_any(event, move |event| {
+            handler(serde_json::from_str(event.payload()).unwrap_or_default());
+        })
+    }
+
+    fn unsubscribe(&self, id: u32) {
+        self.unlisten(id);
+    }
+
+    fn app_handle(&self) -> Option<AppHandle> {
+        Some(self.clone())
+    }
+}
+
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+// Tauri commands exposed to frontend
+#[tauri::command]
+fn get_tunnel_url(state: tauri::State<AppState>) -> Option<String> {
+    state.tunnel_url.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn is_server_running(state: tauri::State<AppState>) -> bool {
+    state.server_process.lock().unwrap().is_some()
+}
+
+#[tauri::command]
+fn restart_server(app: AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
+    stop_server_internal(&state);
+    start_server_internal(&app, &state).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn stop_server(app: AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
+    stop_server_internal(&state);
+    let _ = app.emit("server-status", "stopped");
+    Ok(())
+}
+
+#[tauri::command]
+fn start_tunnel(app: AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
+    start_tunnel_internal(&app, &state).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn stop_tunnel(app: AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
+    stop_tunnel_internal(&state);
+    let _ = app.emit("tunnel-status", "stopped");
+    Ok(())
+}
+
+#[tauri::command]
+fn restart_tunnel(app: AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
+    stop_tunnel_internal(&state);
+    start_tunnel_internal(&app, &state).map_err(|e| e.to_string())
+}
+
+// Tunnel URL with the session's access token, for handing to a new device
+#[tauri::command]
+fn get_share_url(state: tauri::State<AppState>) -> Option<String> {
+    share_url(&state)
+}
+
+// Writes the shareable URL to the system clipboard and returns it
+#[tauri::command]
+fn copy_tunnel_url(app: AppHandle, state: tauri::State<AppState>, clear_after: Option<u64>) -> Result<String, String> {
+    copy_share_url(&app, &state, clear_after)
+}
+
+fn redacting_target(kind: TargetKind) -> Target {
+    Target::new(kind).format(|out, message, _record| {
+        out.finish(format_args!("{}", redact::redact(&message.to_string())))
+    })
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+// capabilities/default.json only covers the bundled server; grant the same
+// permissions to an external server's origin so its UI can call commands.
+// A remote capability opens every app command to that origin, so only a
+// server on this machine gets it; a remote one's page can't reach the app.
+fn allow_remote_ipc(app: &AppHandle, url: &str) -> tauri::Result<()> {
+    if !is_loopback_host(&settings::get().server.external_host) {
+        log::warn!("Not allowing app commands from {}: the server is not on this machine", url);
+        return Ok(());
+    }
+    let permissions = [
+        "core:default",
+        "core:window:allow-close",
+        "core:window:allow-hide",
+        "core:window:allow-show",
+        "core:window:allow-minimize",
+        "core:window:allow-maximize",
+        "core:window:allow-set-focus",
+        "core:window:allow-set-background-color",
+        "core:window:allow-start-dragging",
+        "shell:allow-open",
+        "process:allow-exit",
+        "process:allow-restart",
+    ];
+    let capability = permissions.into_iter().fold(
+        CapabilityBuilder::new("external-server").remote(url.to_string()).window("main"),
+        |capability, permission| capability.permission(permission),
+    );
+    app.add_capability(capability)
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        // Registered first so a second launch hands its arguments to the running
+        // instance and exits before setup spawns or kills any processes
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            log::info!("Second launch handed off (args: {:?}, cwd: {})", args, cwd);
+            // A login item firing while we already run should not pop the window
+            if !args.iter().any(|arg| arg == autostart::HIDDEN_ARG) {
+                show_main_window(app);
+            }
+            let _ = app.emit("second-instance", serde_json::json!({ "args": args, "cwd": cwd }));
+        }))
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(AppState::default())
+        .manage(updates::UpdateState::default())
+        .manage(tray::TrayStatus::default())
+        .manage(notifications::Notifier::default())
+        .invoke_handler(tauri::generate_handler![
+            get_tunnel_url,
+            is_server_running,
+            restart_server,
+            stop_server,
+            start_tunnel,
+            stop_tunnel,
+            restart_tunnel,
+            copy_tunnel_url,
+            get_share_url,
+            qr::get_tunnel_qr,
+            lan::get_lan_urls,
+            crate::get_app_version,
+            updates::check_for_updates,
+            updates::install_update,
+            updates::get_update_channel,
+            updates::set_update_channel,
+            updates::set_update_endpoint,
+            updates::download_update,
+            updates::get_staged_update,
+            updates::install_staged_update,
+            updates::cancel_update_download,
+            updates::install_update_when_idle,
+            updates::cancel_idle_install,
+            updates::set_update_idle_seconds,
+            tray::get_tray_enabled,
+            tray::set_tray_enabled,
+            notifications::get_notification_preferences,
+            notifications::set_notification_preferences,
+            autostart::get_autostart,
+            settings::get_settings,
+            settings::update_settings,
+            autostart::set_autostart,
+            updates::set_update_check_interval,
+            updates::skip_update_version,
+            updates::clear_skipped_updates,
+            crate::is_claude_code_installed,
+            crate::is_claude_mem_installed,
+            request_folder_access,
+            crate::get_log_level,
+            crate::set_log_level,
+            logs::list_log_files,
+            logs::read_log,
+            logs::tail_log,
+            logs::search_log,
+            logs::follow_log,
+            logs::unfollow_log,
+            diagnostics::export_diagnostics,
+            redact::get_redaction_patterns,
+            redact::set_redaction_patterns,
+            secrets::set_secret,
+            secrets::delete_secret,
+            secrets::has_secret,
+            monitor::get_process_stats,
+            monitor::get_resource_thresholds,
+            monitor::set_resource_thresholds,
+        ])
+        .setup(|app| {
+            // Always log to app.log next to server.log (cloudflared output goes to
+            // tunnel.log); also echo to stdout in debug mode. The dispatch accepts
+            // every level so set_log_level can raise or lower the global max level
+            // at runtime. Every target scrubs secrets from the formatted line.
+            let mut log_builder = tauri_plugin_log::Builder::default()
+                .clear_targets()
+                .level(log::LevelFilter::Trace)
+                .max_file_size(5 * 1024 * 1024)
+                .rotation_strategy(RotationStrategy::KeepSome(3))
+                .target(
+                    redacting_target(TargetKind::Folder {
+                        path: default_log_dir(),
+                        file_name: Some("app".to_string()),
+                    })
+                    .filter(|metadata| metadata.target() != "cloudflared"),
+                )
+                .target(
+                    redacting_target(TargetKind::Folder {
+                        path: default_log_dir(),
+                        file_name: Some("tunnel".to_string()),
+                    })
+                    .filter(|metadata| metadata.target() == "cloudflared"),
+                );
+            if cfg!(debug_assertions) {
+                log_builder = log_builder.target(redacting_target(TargetKind::Stdout));
+            }
+            app.handle().plugin(log_builder.build())?;
+            // Log level and extra redaction patterns from settings
+            settings::apply_runtime(&settings::get());
+            settings::attach(app.handle().clone_host());
+            settings::report_load();
+
+            // The single-instance lock only covers the GUI; a running daemon owns
+            // the server, tunnel and port, so tell the user and leave them alone
+            #[cfg(unix)]
+            if control::instance_running() {
+                use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+                log::error!("Terminal Tunnel is already running ({:?} is live); exiting", control::socket_path());
+                app.dialog()
+                    .message("Terminal Tunnel is already running in the background (terminal-tunnel-daemon). Stop it with `terminal-tunnel stop` before opening the app.")
+                    .title("Terminal Tunnel")
+                    .kind(MessageDialogKind::Error)
+                    .show(|_| std::process::exit(1));
+                return Ok(());
+            }
+
+            // Tray icon with live server/tunnel status (can be turned off in settings)
+            tray::setup(app.handle());
+
+            // Native notifications for tunnel, server and update events
+            notifications::setup(app.handle());
+
+            qr::spawn_regenerator(app.handle().clone_host());
+
+            // Periodic update checks that announce new versions via `update-available`
+            updates::spawn_update_scheduler(app.handle().clone());
+
+            // Local JSON-RPC socket for the `terminal-tunnel` CLI and other tools
+            #[cfg(unix)]
+            control::spawn_control_server(app.handle().clone_host());
+
+            // Start server and tunnel on app launch
+            let app_handle = app.handle().clone();
+
+            // Spawn initialization in background to not block app startup
+            thread::spawn(move || {
+                let state = app_handle.state::<AppState>();
+                start_services(&app_handle, &state, |server_ready| {
+                    // Navigate webview to the correct frontend URL
+                    // Development: Vite dev server on 5173
+                    // Production: bundled server on 3456 (serves the React app + API)
+                    // External: the attached server, wherever it runs
+                    let Some(window) = app_handle.get_webview_window("main") else {
+                        return;
+                    };
+                    let target = if settings::get().server.external {
+                        let url = server_url();
+                        if let Err(e) = allow_remote_ipc(&app_handle, &url) {
+                            log::error!("Failed to allow commands from {}: {}", url, e);
+                        }
+                        server_ready.then_some(url)
+                    } else if cfg!(debug_assertions) {
+                        Some("http://127.0.0.1:3456".to_string())
+                    } else {
+                        server_ready.then(|| "http://localhost:3456".to_string())
+                    };
+                    if let Some(url) = target {
+                        log::info!("Navigating webview to {}", url);
+                        let _ = window.eval(&format!("window.location.replace({})", serde_json::Value::from(url)));
+                    }
+                });
+            });
+
+            // The window is created hidden; a login item launch keeps it that way
+            if autostart::launched_hidden() {
+                log::info!("Started hidden by login item");
+            } else {
+                show_main_window(app.handle());
+            }
+
+            // Handle window close to hide instead of quit
+            if let Some(window) = app.get_webview_window("main") {
+                let window_clone = window.clone();
+                window.on_window_event(move |event| {
+                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                        api.prevent_close();
+                        let _ = window_clone.hide();
+                    }
+                });
+            }
+
+            Ok(())
+        })
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app_handle, event| {
+            // Stop child processes on quit, then apply any staged update
+            if let tauri::RunEvent::Exit = event {
+                let state = app_handle.state::<AppState>();
+                lan::stop(app_handle, &state);
+                stop_tunnel_internal(&state);
+                stop_server_internal(&state);
+                #[cfg(unix)]
+                control::remove_socket();
+                updates::install_staged_on_exit(app_handle);
+            }
+        });
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::PathBuf;
+use serde::Serialize;
+
+// Passed by the login item; the window stays hidden while the setup thread
+// brings up the server and tunnel
+pub const HIDDEN_ARG: &str = "--hidden";
+
+#[derive(Clone, Serialize)]
+pub struct AutostartStatus {
+    pub enabled: bool,
+    pub start_hidden: bool,
+}
+
+#[cfg(feature = "gui")]
+pub fn launched_hidden() -> bool {
+    std::env::args().skip(1).any(|arg| arg == HIDDEN_ARG)
+}
+
+// The AppImage path survives updates; the extracted binary inside it does not
+fn launch_path() -> Result<PathBuf, String> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    std::env::current_exe().map_err(|e| format!("Failed to locate app binary: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+fn entry_path() -> Result<PathBuf, String> {
+    let home = std::env::var("HOME").map_err(|_| "Could not determine home directory")?;
+    Ok(PathBuf::from(home).join("Library").join("LaunchAgents").join("com.terminaltunnel.app.plist"))
+}
+
+#[cfg(target_os = "linux")]
+fn entry_path() -> Result<PathBuf, String> {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let config = std::env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(home).join(".config"));
+    Ok(config.join("autostart").join("terminal-tunnel.desktop"))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn entry_path() -> Result<PathBuf, String> {
+    Err("Launch at login is only supported on macOS and Linux".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// LaunchAgent run once at login; KeepAlive stays off so quitting from the tray sticks
+#[cfg(target_os = "macos")]
+fn entry_contents(program: &str, start_hidden: bool) -> String {
+    let mut arguments = format!("        <string>{}</string>\n", xml_escape(program));
+    if start_hidden {
+        arguments.push_str(&format!("        <string>{}</string>\n", HIDDEN_ARG));
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.terminaltunnel.app</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>ProcessType</key>
+    <string>Interactive</string>
+</dict>
+</plist>
+"#,
+        arguments
+    )
+}
+
+#[cfg(not(target_os = "macos"))]
+fn entry_contents(program: &str, start_hidden: bool) -> String {
+    // Exec is unescaped twice: first as a desktop entry string value (\\ and
+    // the \n-style escapes), then as a quoted argument (\ before " ` $ \).
+    // Field codes start with %, so a literal one is written %%.
+    let mut quoted = String::from("\"");
+    for c in program.chars() {
+        if matches!(c, '\\' | '"' | '`' | '$') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    let mut exec = String::new();
+    for c in quoted.chars() {
+        match c {
+            '\\' => exec.push_str("\\\\"),
+            '\n' => exec.push_str("\\n"),
+            '\t' => exec.push_str("\\t"),
+            '\r' => exec.push_str("\\r"),
+            '%' => exec.push_str("%%"),
+            c => exec.push(c),
+        }
+    }
+    if start_hidden {
+        exec.push(' ');
+        exec.push_str(HIDDEN_ARG);
+    }
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Terminal Tunnel\n\
+         Comment=Start Terminal Tunnel at login\n\
+         Exec={}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec
+    )
+}
+
+pub fn autostart_status() -> AutostartStatus {
+    let contents = entry_path().ok().and_then(|path| fs::read_to_string(path).ok());
+    AutostartStatus {
+        enabled: contents.is_some(),
+        start_hidden: contents.is_some_and(|contents| contents.contains(HIDDEN_ARG)),
+    }
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn get_autostart() -> AutostartStatus {
+    autostart_status()
+}
+
+// start_hidden defaults to the current entry's setting (hidden for a new one)
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn set_autostart(enabled: bool, start_hidden: Option<bool>) -> Result<AutostartStatus, String> {
+    let path = entry_path()?;
+
+    if !enabled {
+        match fs::remove_file(&path) {
+            Ok(()) => log::info!("Removed login item {:?}", path),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(format!("Failed to remove {:?}: {}", path, err)),
+        }
+        return Ok(autostart_status());
+    }
+
+    let current = autostart_status();
+    let start_hidden = start_hidden.unwrap_or(!current.enabled || current.start_hidden);
+    let program = launch_path()?;
+    let contents = entry_contents(&program.to_string_lossy(), start_hidden);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    log::info!("Registered login item {:?} (start hidden: {})", path, start_hidden);
+    Ok(autostart_status())
+}
@@ -0,0 +1,5 @@
+// Headless supervisor for the server, PTY sidecar and tunnel. Shares config
+// and logs with the desktop app; stop it with SIGINT or SIGTERM.
+fn main() {
+  app_lib::run_daemon();
+}
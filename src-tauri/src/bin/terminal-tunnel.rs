@@ -0,0 +1,4 @@
+// Command-line client for a running Terminal Tunnel app or daemon
+fn main() {
+  app_lib::run_cli();
+}
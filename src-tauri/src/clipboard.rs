@@ -0,0 +1,137 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+#[cfg(feature = "gui")]
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::{settings, Host};
+
+// Bumped on every copy so an older auto-clear timer leaves newer contents alone
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Auto-clear delay when the caller gives none (MT_CLIPBOARD_CLEAR_SECONDS overrides)
+pub fn default_clear_after() -> Option<u64> {
+    settings::get().clipboard.clear_after_seconds
+}
+
+// (program, args) for a clipboard command-line tool
+type Tool = (&'static str, &'static [&'static str]);
+
+// Tools that copy stdin, in order of preference
+fn write_tools() -> Vec<Tool> {
+    let mut tools: Vec<Tool> = Vec::new();
+    if cfg!(target_os = "macos") {
+        tools.push(("pbcopy", &[]));
+    } else if cfg!(windows) {
+        tools.push(("clip", &[]));
+    } else {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            tools.push(("wl-copy", &[]));
+        }
+        tools.push(("xclip", &["-selection", "clipboard"]));
+        tools.push(("xsel", &["--clipboard", "--input"]));
+    }
+    tools
+}
+
+fn read_tools() -> Vec<Tool> {
+    let mut tools: Vec<Tool> = Vec::new();
+    if cfg!(target_os = "macos") {
+        tools.push(("pbpaste", &[]));
+    } else if !cfg!(windows) {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            tools.push(("wl-paste", &["--no-newline"]));
+        }
+        tools.push(("xclip", &["-selection", "clipboard", "-o"]));
+        tools.push(("xsel", &["--clipboard", "--output"]));
+    }
+    tools
+}
+
+fn write_with_tools(text: &str) -> Result<(), String> {
+    let mut last_error = "no clipboard tool available".to_string();
+    for (program, args) in write_tools() {
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                last_error = format!("{}: {}", program, err);
+                continue;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        match child.wait() {
+            Ok(status) if status.success() => return Ok(()),
+            Ok(status) => last_error = format!("{} exited with {}", program, status),
+            Err(err) => last_error = format!("{}: {}", program, err),
+        }
+    }
+    Err(last_error)
+}
+
+fn read_with_tools() -> Option<String> {
+    read_tools().into_iter().find_map(|(program, args)| {
+        let output = Command::new(program).args(args).stderr(Stdio::null()).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    })
+}
+
+// Native clipboard through the plugin when there is a window; system tools
+// otherwise, and on Wayland sessions where the native write fails
+fn write(host: &dyn Host, text: &str) -> Result<(), String> {
+    #[cfg(feature = "gui")]
+    if let Some(app) = host.app_handle() {
+        match app.clipboard().write_text(text) {
+            Ok(()) => return Ok(()),
+            Err(err) => log::warn!("Native clipboard write failed ({}); trying system tools", err),
+        }
+    }
+    #[cfg(not(feature = "gui"))]
+    let _ = host;
+    write_with_tools(text).map_err(|e| format!("Failed to write clipboard: {}", e))
+}
+
+fn read(host: &dyn Host) -> Option<String> {
+    #[cfg(feature = "gui")]
+    if let Some(text) = host.app_handle().and_then(|app| app.clipboard().read_text().ok()) {
+        return Some(text);
+    }
+    #[cfg(not(feature = "gui"))]
+    let _ = host;
+    read_with_tools()
+}
+
+// Copy text and, with clear_after, empty the clipboard after that many seconds
+// unless something else has been copied meanwhile
+pub fn copy(host: &dyn Host, text: &str, clear_after: Option<u64>) -> Result<(), String> {
+    write(host, text)?;
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+
+    if let Some(seconds) = clear_after.filter(|seconds| *seconds > 0) {
+        let host: Arc<dyn Host> = host.clone_host();
+        let text = text.to_string();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_secs(seconds));
+            if GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            if read(host.as_ref()).is_some_and(|current| current.trim_end() == text) {
+                match write(host.as_ref(), "") {
+                    Ok(()) => log::info!("Cleared clipboard after {}s", seconds),
+                    Err(err) => log::warn!("Failed to clear clipboard: {}", err),
+                }
+            }
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,415 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::rpc::{self, RpcError, INVALID_REQUEST, PARSE_ERROR};
+use crate::{default_config_dir, Host};
+
+// Restarting the tunnel waits up to 40s for cloudflared
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_LOG_LINES: usize = 100;
+
+// Set once this process owns the socket file
+static SERVING: AtomicBool = AtomicBool::new(false);
+
+// Events a client may subscribe to; the same ones the webview receives
+const SUBSCRIBABLE_EVENTS: [&str; 15] = [
+    "server-status",
+    "tunnel-status",
+    "tunnel-url",
+    "tunnel-qr",
+    "lan-urls",
+    "resource-warning",
+    "settings-changed",
+    "log-line",
+    "update-available",
+    "update-download-progress",
+    "update-staged",
+    "update-installing",
+    "update-error",
+    "update-cancelled",
+    "update-idle-countdown",
+];
+
+const USAGE: &str = "Usage: terminal-tunnel <command>
+
+Commands:
+  status                  Server and tunnel state
+  url                     Current tunnel URL, including its access token
+  lan-urls                Local network URLs while LAN mode is on
+  restart-server          Restart the local server
+  restart-tunnel          Restart the tunnel and print the new URL
+  logs [name] [lines]     Last lines of app, server, sidecar or tunnel log
+  stop                    Quit the running app or daemon";
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: Option<String>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    // Absent for notifications, which get no response
+    id: Option<Value>,
+}
+
+enum Outgoing {
+    Line(String),
+    // Sent after the `quit` response so it is written before the process exits
+    Shutdown,
+}
+
+pub fn socket_path() -> PathBuf {
+    std::env::var("MT_CONTROL_SOCKET")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_config_dir().join("run").join("control.sock"))
+}
+
+// Someone is answering on the socket: the app or a daemon is already running
+pub fn instance_running() -> bool {
+    UnixStream::connect(socket_path()).is_ok()
+}
+
+// Only the owner removes it; another instance may be serving the same path
+pub fn remove_socket() {
+    if SERVING.swap(false, Ordering::SeqCst) {
+        let _ = fs::remove_file(socket_path());
+    }
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> String {
+    let message = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    };
+    message.to_string()
+}
+
+// Per-connection event subscriptions as (event, listener id), plus the log
+// follows started on the connection so they stop when it closes
+struct Subscriptions {
+    host: Arc<dyn Host>,
+    sender: mpsc::Sender<Outgoing>,
+    active: Vec<(String, u32)>,
+    follows: Vec<u32>,
+}
+
+impl Subscriptions {
+    fn subscribe(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let requested: Option<Vec<String>> = rpc::param(params, "events")?;
+        let events = requested
+            .unwrap_or_else(|| SUBSCRIBABLE_EVENTS.iter().map(|event| event.to_string()).collect());
+        if let Some(unknown) = events.iter().find(|event| !SUBSCRIBABLE_EVENTS.contains(&event.as_str())) {
+            return Err(RpcError::new(rpc::INVALID_PARAMS, format!("Unknown event: {}", unknown)));
+        }
+
+        for event in &events {
+            if self.active.iter().any(|(active, _)| active == event) {
+                continue;
+            }
+            let sender = self.sender.clone();
+            let name = event.clone();
+            let id = self.host.subscribe(event, Box::new(move |payload| {
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "event",
+                    "params": { "event": name, "payload": payload },
+                });
+                let _ = sender.send(Outgoing::Line(notification.to_string()));
+            }));
+            self.active.push((event.clone(), id));
+        }
+        Ok(json!({ "events": events }))
+    }
+
+    fn unsubscribe(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let events: Option<Vec<String>> = rpc::param(params, "events")?;
+        let host = Arc::clone(&self.host);
+        self.active.retain(|(event, id)| {
+            let remove = events.as_ref().map_or(true, |events| events.contains(event));
+            if remove {
+                host.unsubscribe(*id);
+            }
+            !remove
+        });
+        Ok(Value::Null)
+    }
+
+    fn follow_log(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let result = rpc::call(self.host.as_ref(), "follow_log", params)?;
+        if let Some(id) = result.as_u64() {
+            self.follows.push(id as u32);
+        }
+        Ok(result)
+    }
+
+    fn unfollow_log(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let result = rpc::call(self.host.as_ref(), "unfollow_log", params)?;
+        let subscription: Option<u32> = rpc::param(params, "subscription")?;
+        self.follows.retain(|id| Some(*id) != subscription);
+        Ok(result)
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        for (_, id) in self.active.drain(..) {
+            self.host.unsubscribe(id);
+        }
+        for id in self.follows.drain(..) {
+            let _ = rpc::call(self.host.as_ref(), "unfollow_log", &json!({ "subscription": id }));
+        }
+    }
+}
+
+// Newline-delimited JSON-RPC 2.0. Requests on one connection run in order;
+// event notifications interleave with responses.
+fn handle_connection(host: Arc<dyn Host>, stream: UnixStream) -> std::io::Result<()> {
+    let (sender, receiver) = mpsc::channel::<Outgoing>();
+    let mut writer = stream.try_clone()?;
+    let writer_host = host.clone_host();
+    thread::spawn(move || {
+        for outgoing in receiver {
+            match outgoing {
+                Outgoing::Line(line) => {
+                    if writeln!(writer, "{}", line).is_err() {
+                        break;
+                    }
+                }
+                Outgoing::Shutdown => {
+                    log::info!("Quit requested over control socket");
+                    writer_host.shutdown();
+                }
+            }
+        }
+    });
+
+    let mut subscriptions = Subscriptions {
+        host: host.clone_host(),
+        sender: sender.clone(),
+        active: Vec::new(),
+        follows: Vec::new(),
+    };
+
+    for line in BufReader::new(&stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(err) => {
+                let error = RpcError::new(PARSE_ERROR, err.to_string());
+                let _ = sender.send(Outgoing::Line(response(Value::Null, Err(error))));
+                continue;
+            }
+        };
+
+        let result = if request.jsonrpc.as_deref() != Some("2.0") {
+            Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""))
+        } else {
+            match request.method.as_str() {
+                "subscribe" => subscriptions.subscribe(&request.params),
+                "unsubscribe" => subscriptions.unsubscribe(&request.params),
+                "follow_log" => subscriptions.follow_log(&request.params),
+                "unfollow_log" => subscriptions.unfollow_log(&request.params),
+                "quit" => Ok(Value::Null),
+                method => rpc::call(host.as_ref(), method, &request.params),
+            }
+        };
+
+        let quit = request.method == "quit" && result.is_ok();
+        if let Some(id) = request.id {
+            let _ = sender.send(Outgoing::Line(response(id, result)));
+        }
+        if quit {
+            let _ = sender.send(Outgoing::Shutdown);
+        }
+    }
+    Ok(())
+}
+
+// The socket lives in an owner-only directory so no other user can connect,
+// even between bind and chmod. Only our own run/ dir is created and locked
+// down; a directory picked with MT_CONTROL_SOCKET is checked but left alone.
+fn prepare_socket_dir(path: &Path) -> Result<(), String> {
+    let Some(parent) = path.parent() else {
+        return Ok(());
+    };
+    if std::env::var_os("MT_CONTROL_SOCKET").is_none() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        return fs::set_permissions(parent, fs::Permissions::from_mode(0o700)).map_err(|e| e.to_string());
+    }
+
+    let metadata = fs::metadata(parent).map_err(|e| format!("{:?}: {}", parent, e))?;
+    if !metadata.is_dir() {
+        return Err(format!("{:?} is not a directory", parent));
+    }
+    // SAFETY: geteuid has no preconditions and cannot fail
+    if metadata.uid() != unsafe { libc::geteuid() } {
+        return Err(format!("{:?} is owned by another user", parent));
+    }
+    if metadata.mode() & 0o022 != 0 {
+        return Err(format!("{:?} is writable by other users; use an owner-only directory", parent));
+    }
+    Ok(())
+}
+
+pub fn spawn_control_server(host: Arc<dyn Host>) {
+    let path = socket_path();
+
+    // A live socket belongs to another instance; a dead one is left over from a crash
+    if instance_running() {
+        log::warn!("Control socket {:?} is already served by another instance", path);
+        return;
+    }
+    let _ = fs::remove_file(&path);
+    if let Err(err) = prepare_socket_dir(&path) {
+        log::error!("Failed to prepare control socket directory for {:?}: {}", path, err);
+        return;
+    }
+
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(err) => {
+            log::error!("Failed to bind control socket {:?}: {}", path, err);
+            return;
+        }
+    };
+    if let Err(err) = fs::set_permissions(&path, fs::Permissions::from_mode(0o600)) {
+        log::warn!("Failed to restrict control socket permissions: {}", err);
+    }
+    SERVING.store(true, Ordering::SeqCst);
+    log::info!("Control socket listening at {:?}", path);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let host = host.clone_host();
+                    thread::spawn(move || {
+                        if let Err(err) = handle_connection(host, stream) {
+                            log::warn!("Control connection failed: {}", err);
+                        }
+                    });
+                }
+                Err(err) => log::warn!("Control socket accept failed: {}", err),
+            }
+        }
+    });
+}
+
+// Minimal client used by the CLI: one connection, requests answered in order
+struct Client {
+    stream: UnixStream,
+    reader: BufReader<UnixStream>,
+    next_id: u64,
+}
+
+impl Client {
+    fn connect() -> std::io::Result<Self> {
+        let stream = UnixStream::connect(socket_path())?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(Self { stream, reader, next_id: 0 })
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> std::io::Result<Result<Value, String>> {
+        self.next_id += 1;
+        let request = json!({ "jsonrpc": "2.0", "id": self.next_id, "method": method, "params": params });
+        writeln!(self.stream, "{}", request)?;
+
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            let message: Value = serde_json::from_str(&line).map_err(std::io::Error::other)?;
+            if message.get("id") != Some(&json!(self.next_id)) {
+                continue;
+            }
+            return Ok(match message.get("error") {
+                Some(error) => Err(error["message"].as_str().unwrap_or("Unknown error").to_string()),
+                None => Ok(message.get("result").cloned().unwrap_or_default()),
+            });
+        }
+    }
+}
+
+fn run_command(client: &mut Client, args: &[String]) -> std::io::Result<Result<Value, String>> {
+    let command = args[0].as_str();
+    match command {
+        "status" => client.call("status", Value::Null),
+        "url" => Ok(client.call("get_share_url", Value::Null)?.and_then(|url| {
+            if url.is_null() {
+                Err("No tunnel URL available".to_string())
+            } else {
+                Ok(url)
+            }
+        })),
+        "lan-urls" => client.call("get_lan_urls", Value::Null),
+        "restart-server" => client.call("restart_server", Value::Null),
+        "restart-tunnel" => match client.call("restart_tunnel", Value::Null)? {
+            Ok(_) => client.call("get_share_url", Value::Null),
+            Err(err) => Ok(Err(err)),
+        },
+        "logs" => {
+            let lines = match args.get(2) {
+                Some(lines) => match lines.parse::<usize>() {
+                    Ok(lines) => lines,
+                    Err(_) => return Ok(Err(format!("Invalid line count: {}", lines))),
+                },
+                None => DEFAULT_LOG_LINES,
+            };
+            let name = args.get(1).map(String::as_str).unwrap_or("app");
+            client.call("tail_log", json!({ "name": name, "lines": lines }))
+        }
+        "stop" => client.call("quit", Value::Null),
+        _ => Ok(Err(format!("Unknown command: {}\n\n{}", command, USAGE))),
+    }
+}
+
+pub fn run_cli() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || matches!(args[0].as_str(), "help" | "-h" | "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let result = Client::connect().and_then(|mut client| run_command(&mut client, &args));
+    let value = match result {
+        Ok(Ok(value)) => value,
+        Ok(Err(err)) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("Could not reach Terminal Tunnel at {:?}: {}", socket_path(), err);
+            eprintln!("Is the app or terminal-tunnel-daemon running?");
+            std::process::exit(1);
+        }
+    };
+
+    match value {
+        Value::Null => {}
+        Value::String(text) => println!("{}", text),
+        Value::Array(lines) => {
+            for line in lines {
+                println!("{}", line.as_str().map(str::to_string).unwrap_or_else(|| line.to_string()));
+            }
+        }
+        other => println!("{}", serde_json::to_string_pretty(&other).unwrap_or_default()),
+    }
+}
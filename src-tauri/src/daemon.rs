@@ -0,0 +1,202 @@
+use std::fs;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use time::{macros::format_description, OffsetDateTime};
+use tokio::sync::Notify;
+
+#[cfg(unix)]
+use crate::control;
+use crate::{
+    default_log_dir, lan, qr, redact, settings, start_services, stop_server_internal,
+    stop_tunnel_internal, AppState, EventHandler, Host,
+};
+
+// Stands in for Tauri's event system so control socket clients can subscribe
+#[derive(Default)]
+struct EventBus {
+    next_id: AtomicU32,
+    handlers: Mutex<Vec<(u32, String, Arc<EventHandler>)>>,
+}
+
+// Runs the process supervision without Tauri
+struct DaemonHost {
+    resource_dir: Option<PathBuf>,
+    state: Arc<AppState>,
+    shutdown: Arc<Notify>,
+    events: Arc<EventBus>,
+}
+
+impl Host for DaemonHost {
+    fn resource_dir(&self) -> Option<PathBuf> {
+        self.resource_dir.clone()
+    }
+
+    fn publish(&self, event: &str, payload: serde_json::Value) {
+        log::debug!("{}: {}", event, payload);
+        // Call handlers outside the lock so they can (un)subscribe
+        let handlers: Vec<Arc<EventHandler>> = self.events.handlers.lock().unwrap()
+            .iter()
+            .filter(|(_, name, _)| name == event)
+            .map(|(_, _, handler)| Arc::clone(handler))
+            .collect();
+        for handler in handlers {
+            handler(payload.clone());
+        }
+    }
+
+    fn clone_host(&self) -> Arc<dyn Host> {
+        Arc::new(DaemonHost {
+            resource_dir: self.resource_dir.clone(),
+            state: Arc::clone(&self.state),
+            shutdown: Arc::clone(&self.shutdown),
+            events: Arc::clone(&self.events),
+        })
+    }
+
+    fn app_state(&self) -> &AppState {
+        &self.state
+    }
+
+    fn shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+
+    fn subscribe(&self, event: &str, handler: EventHandler) -> u32 {
+        let id = self.events.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.events.handlers.lock().unwrap().push((id, event.to_string(), Arc::new(handler)));
+        id
+    }
+
+    fn unsubscribe(&self, id: u32) {
+        self.events.handlers.lock().unwrap().retain(|(handler_id, _, _)| *handler_id != id);
+    }
+}
+
+// Where Tauri puts bundle resources relative to the executable: Contents/Resources
+// on macOS, /usr/lib/<product name> for Linux packages. MT_RESOURCE_DIR overrides it.
+fn bundled_resource_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("MT_RESOURCE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+
+    let exe = std::env::current_exe().ok()?;
+    let exe_dir = exe.parent()?;
+    if cfg!(target_os = "macos") {
+        return Some(exe_dir.parent()?.join("Resources"));
+    }
+    let packaged = exe_dir.parent()?.join("lib").join("Terminal Tunnel");
+    if packaged.is_dir() {
+        Some(packaged)
+    } else {
+        Some(exe_dir.to_path_buf())
+    }
+}
+
+// Same files and line format as the app's log plugin targets. The daemon appends
+// without rotating; the app rotates them on its next launch.
+fn init_logger() -> Result<(), Box<dyn std::error::Error>> {
+    let log_dir = default_log_dir();
+    fs::create_dir_all(&log_dir)?;
+
+    let timestamp = format_description!("[[[year]-[month]-[day]][[[hour]:[minute]:[second]]");
+    let mut dispatch = fern::Dispatch::new()
+        .format(move |out, message, record| {
+            out.finish(format_args!(
+                "{}[{}][{}] {}",
+                OffsetDateTime::now_utc().format(&timestamp).unwrap_or_default(),
+                record.target(),
+                record.level(),
+                redact::redact(&message.to_string())
+            ))
+        })
+        .level(log::LevelFilter::Trace)
+        .chain(
+            fern::Dispatch::new()
+                .filter(|metadata| metadata.target() != "cloudflared")
+                .chain(fern::log_file(log_dir.join("app.log"))?),
+        )
+        .chain(
+            fern::Dispatch::new()
+                .filter(|metadata| metadata.target() == "cloudflared")
+                .chain(fern::log_file(log_dir.join("tunnel.log"))?),
+        );
+    // Under launchd/systemd stdout is usually captured already
+    if std::io::stdout().is_terminal() {
+        dispatch = dispatch.chain(std::io::stdout());
+    }
+    dispatch.apply()?;
+    Ok(())
+}
+
+fn wait_for_shutdown(requested: &Notify) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build signal runtime");
+
+    runtime.block_on(async {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let mut terminate = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+                _ = requested.notified() => {}
+            }
+        }
+        #[cfg(not(unix))]
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = requested.notified() => {}
+        }
+    });
+}
+
+pub fn run() {
+    if let Err(err) = init_logger() {
+        eprintln!("Failed to open log files in {:?}: {}", default_log_dir(), err);
+    }
+    settings::apply_runtime(&settings::get());
+    settings::report_load();
+    log::info!("Terminal Tunnel daemon {} starting", env!("CARGO_PKG_VERSION"));
+
+    // Same rule as the app's single-instance lock: never touch another
+    // instance's processes
+    #[cfg(unix)]
+    if control::instance_running() {
+        log::error!("Terminal Tunnel is already running ({:?} is live); exiting", control::socket_path());
+        std::process::exit(1);
+    }
+
+    let host = Arc::new(DaemonHost {
+        resource_dir: bundled_resource_dir(),
+        state: Arc::new(AppState::default()),
+        shutdown: Arc::new(Notify::new()),
+        events: Arc::new(EventBus::default()),
+    });
+    log::info!("Resource dir: {:?}", host.resource_dir);
+    settings::attach(host.clone_host());
+
+    #[cfg(unix)]
+    control::spawn_control_server(host.clone_host());
+    qr::spawn_regenerator(host.clone_host());
+
+    {
+        let host = Arc::clone(&host);
+        thread::spawn(move || start_services(host.as_ref(), &host.state, |_| {}));
+    }
+
+    wait_for_shutdown(&host.shutdown);
+
+    log::info!("Shutdown requested, stopping processes");
+    lan::stop(host.as_ref(), &host.state);
+    stop_tunnel_internal(&host.state);
+    stop_server_internal(&host.state);
+    #[cfg(unix)]
+    control::remove_socket();
+    log::info!("Terminal Tunnel daemon stopped");
+}
@@ -0,0 +1,222 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    default_log_dir, find_project_root, logs, redact, resolve_cloudflared_path,
+    resolve_lsof_path, server_url, settings, AppState,
+};
+
+// Environment variable names containing any of these are masked in the bundle
+const SECRET_ENV_MARKERS: [&str; 9] = [
+    "TOKEN", "SECRET", "PASSWORD", "PASSWD", "KEY", "AUTH", "COOKIE", "CREDENTIAL", "PIN",
+];
+
+const DIAGNOSTIC_PORTS: [u16; 2] = [3456, 3457];
+
+// The staging dir sits in the shared temp dir, so it must be new and ours:
+// create_dir fails if someone else made the name first
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(path)
+}
+
+fn process_snapshot(process: &Mutex<Option<Child>>) -> Value {
+    let mut guard = process.lock().unwrap();
+    match guard.as_mut() {
+        Some(child) => {
+            let exit_status = child.try_wait().ok().flatten().map(|status| status.to_string());
+            json!({
+                "running": exit_status.is_none(),
+                "pid": child.id(),
+                "exit_status": exit_status,
+            })
+        }
+        None => json!({ "running": false, "pid": null, "exit_status": null }),
+    }
+}
+
+fn command_version(program: &Path) -> String {
+    match Command::new(program).arg("--version").output() {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if stdout.is_empty() {
+                String::from_utf8_lossy(&output.stderr).trim().to_string()
+            } else {
+                stdout
+            }
+        }
+        Err(err) => format!("unavailable: {}", err),
+    }
+}
+
+fn resolve_node_path(app: &AppHandle) -> PathBuf {
+    if !cfg!(debug_assertions) {
+        if let Ok(resource_dir) = app.path().resource_dir() {
+            return resource_dir.join("bin").join("node");
+        }
+    }
+    if let Some(project_root) = find_project_root() {
+        let bundled_node = project_root.join("src-tauri").join("bin").join("node");
+        if bundled_node.exists() {
+            return bundled_node;
+        }
+    }
+    PathBuf::from("node")
+}
+
+fn redacted_environment() -> String {
+    let mut vars: Vec<(String, String)> = std::env::vars().collect();
+    vars.sort();
+    vars.into_iter()
+        .map(|(key, value)| {
+            let upper = key.to_uppercase();
+            if SECRET_ENV_MARKERS.iter().any(|marker| upper.contains(marker)) {
+                format!("{}=[REDACTED]\n", key)
+            } else {
+                format!("{}={}\n", key, redact::redact(&value))
+            }
+        })
+        .collect()
+}
+
+fn port_listeners() -> String {
+    let Some(lsof_path) = resolve_lsof_path() else {
+        return "lsof not found\n".to_string();
+    };
+
+    let mut report = String::new();
+    for port in DIAGNOSTIC_PORTS {
+        report.push_str(&format!("## Port {}\n", port));
+        match Command::new(&lsof_path)
+            .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN"])
+            .output()
+        {
+            Ok(output) if !output.stdout.is_empty() => {
+                report.push_str(&String::from_utf8_lossy(&output.stdout));
+            }
+            Ok(_) => report.push_str("no listener\n"),
+            Err(err) => report.push_str(&format!("lsof failed: {}\n", err)),
+        }
+        report.push('\n');
+    }
+    report
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+fn default_destination() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let downloads = PathBuf::from(home).join("Downloads");
+    if downloads.is_dir() {
+        downloads
+    } else {
+        default_log_dir()
+    }
+}
+
+fn build_bundle(app: &AppHandle, destination: PathBuf) -> Result<PathBuf, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let bundle_name = format!("terminal-tunnel-diagnostics-{}", timestamp);
+    let staging_root = std::env::temp_dir().join(format!("{}-staging", bundle_name));
+    create_private_dir(&staging_root)
+        .map_err(|e| format!("Failed to create staging dir {:?}: {}", staging_root, e))?;
+    let staging = staging_root.join(&bundle_name);
+    fs::create_dir_all(staging.join("logs"))
+        .map_err(|e| format!("Failed to create staging dir: {}", e))?;
+
+    // Managed logs, scrubbed again since the server and sidecar write their own
+    for log_file in logs::managed_log_files().into_iter().filter(|log| log.exists) {
+        let target = staging.join("logs").join(format!("{}.log", log_file.name));
+        let copied = fs::read(&log_file.path).and_then(|content| {
+            fs::write(&target, redact::redact(&String::from_utf8_lossy(&content)).as_bytes())
+        });
+        if let Err(err) = copied {
+            log::warn!("Failed to copy {} for diagnostics: {}", log_file.path, err);
+        }
+    }
+
+    // Process status
+    let state = app.state::<AppState>();
+    write_json(&staging.join("status.json"), &json!({
+        "server": process_snapshot(&state.server_process),
+        "sidecar": process_snapshot(&state.sidecar_process),
+        "tunnel": process_snapshot(&state.tunnel_process),
+        "tunnel_url": state.tunnel_url.lock().unwrap().clone(),
+        "external_server": settings::get().server.external,
+        "server_url": server_url(),
+    }))?;
+
+    // Effective settings and which env vars override them
+    write_json(&staging.join("settings.json"), &serde_json::to_value(settings::snapshot()).unwrap_or_default())?;
+
+    // Resolved paths
+    let node_path = resolve_node_path(app);
+    let cloudflared_path = resolve_cloudflared_path(app);
+    write_json(&staging.join("paths.json"), &json!({
+        "resource_dir": app.path().resource_dir().ok(),
+        "project_root": find_project_root(),
+        "log_dir": default_log_dir(),
+        "node": node_path,
+        "cloudflared": cloudflared_path,
+        "lsof": resolve_lsof_path(),
+    }))?;
+
+    // App and bundled tool versions
+    write_json(&staging.join("versions.json"), &json!({
+        "app": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "debug_build": cfg!(debug_assertions),
+        "node": command_version(&node_path),
+        "cloudflared": command_version(Path::new(&cloudflared_path)),
+    }))?;
+
+    fs::write(staging.join("environment.txt"), redacted_environment())
+        .map_err(|e| format!("Failed to write environment: {}", e))?;
+    fs::write(staging.join("ports.txt"), port_listeners())
+        .map_err(|e| format!("Failed to write port info: {}", e))?;
+
+    fs::create_dir_all(&destination)
+        .map_err(|e| format!("Failed to create {:?}: {}", destination, e))?;
+    let archive = destination.join(format!("{}.tar.gz", bundle_name));
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&staging_root)
+        .arg(&bundle_name)
+        .status();
+    let _ = fs::remove_dir_all(&staging_root);
+
+    match status {
+        Ok(status) if status.success() => Ok(archive),
+        Ok(status) => Err(format!("tar exited with {}", status)),
+        Err(err) => Err(format!("Failed to run tar: {}", err)),
+    }
+}
+
+// Bundle logs, process status, paths, versions, environment and ports into a tar.gz
+#[tauri::command]
+pub async fn export_diagnostics(app: AppHandle, destination: Option<String>) -> Result<String, String> {
+    let destination = destination.map(PathBuf::from).unwrap_or_else(default_destination);
+
+    let archive = tauri::async_runtime::spawn_blocking(move || build_bundle(&app, destination))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    log::info!("Diagnostics bundle written to {:?}", archive);
+    Ok(archive.to_string_lossy().to_string())
+}
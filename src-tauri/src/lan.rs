@@ -0,0 +1,236 @@
+use std::net::{IpAddr, SocketAddr};
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use mdns_sd::{IfKind, ServiceDaemon, ServiceInfo};
+
+use crate::settings::{self, LanSettings};
+use crate::{
+    clear_tunnel_token, mint_tunnel_token, start_server_internal, stop_server_internal,
+    wait_for_server_health, AppState, Host, LOCAL_SERVER_PORT,
+};
+
+const SERVICE_TYPE: &str = "_terminaltunnel._tcp.local.";
+
+// Running LAN mode; mdns is None when advertising is off or failed to start
+struct Lan {
+    mdns: Option<(ServiceDaemon, String)>,
+}
+
+static ACTIVE: Mutex<Option<Lan>> = Mutex::new(None);
+
+pub fn is_active() -> bool {
+    ACTIVE.lock().unwrap().is_some()
+}
+
+// The server binds 0.0.0.0 in LAN mode, which is IPv4 only, so IPv6 addresses
+// would give links that are refused
+fn is_lan_address(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !ip.is_loopback() && !ip.is_link_local() && !ip.is_unspecified(),
+        IpAddr::V6(_) => false,
+    }
+}
+
+// Addresses other machines can reach us on. Link-local ones are skipped;
+// phones rarely handle them in a URL.
+pub fn lan_addresses() -> Vec<IpAddr> {
+    let mut addresses: Vec<IpAddr> = match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces
+            .into_iter()
+            .map(|interface| interface.ip())
+            .filter(is_lan_address)
+            .collect(),
+        Err(err) => {
+            log::warn!("Failed to list network interfaces: {}", err);
+            Vec::new()
+        }
+    };
+    addresses.sort();
+    addresses.dedup();
+    addresses
+}
+
+// One URL per LAN address, carrying the access token like share_url does
+pub fn lan_urls(state: &AppState) -> Vec<String> {
+    if !is_active() {
+        return Vec::new();
+    }
+    let token = state.tunnel_token.lock().unwrap().clone();
+    lan_addresses()
+        .into_iter()
+        .map(|ip| {
+            let url = format!("http://{}", SocketAddr::new(ip, LOCAL_SERVER_PORT));
+            match &token {
+                Some(token) => format!("{}/?token={}", url, token),
+                None => url,
+            }
+        })
+        .collect()
+}
+
+// First label of the machine's hostname, reduced to what a .local name allows
+fn host_label() -> String {
+    let hostname = Command::new("hostname")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+    let label: String = hostname
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    if label.is_empty() {
+        "terminal-tunnel".to_string()
+    } else {
+        label
+    }
+}
+
+// Addresses are filled in (and kept current) by the daemon itself, from IPv4
+// interfaces only to match what the server listens on
+fn advertise() -> Result<(ServiceDaemon, String), String> {
+    let daemon = ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS: {}", e))?;
+    daemon
+        .disable_interface(IfKind::IPv6)
+        .map_err(|e| format!("Failed to limit mDNS to IPv4: {}", e))?;
+    let label = host_label();
+    let properties = [("version", env!("CARGO_PKG_VERSION")), ("path", "/")];
+    let info = ServiceInfo::new(
+        SERVICE_TYPE,
+        &format!("Terminal Tunnel on {}", label),
+        &format!("{}.local.", label),
+        "",
+        LOCAL_SERVER_PORT,
+        &properties[..],
+    )
+    .map_err(|e| format!("Invalid mDNS service: {}", e))?
+    .enable_addr_auto();
+    let fullname = info.get_fullname().to_string();
+    daemon.register(info).map_err(|e| format!("Failed to register mDNS service: {}", e))?;
+    log::info!("Advertising {} on the local network", fullname);
+    Ok((daemon, fullname))
+}
+
+// The server listens on all interfaces in LAN mode (see apply_server_env); this
+// gates it with the access token and announces it
+pub fn start(host: &dyn Host, state: &AppState) -> Result<(), String> {
+    let settings = settings::get();
+    let mut active = ACTIVE.lock().unwrap();
+    if active.is_some() {
+        return Ok(());
+    }
+    if settings.server.external {
+        return Err("LAN mode needs the bundled server".to_string());
+    }
+
+    // Other machines on the network need the token whether or not a tunnel is up
+    if state.tunnel_token.lock().unwrap().is_none() {
+        mint_tunnel_token(state)?;
+    }
+
+    let mdns = if settings.lan.advertise {
+        match advertise() {
+            Ok(mdns) => Some(mdns),
+            Err(err) => {
+                log::warn!("LAN mode running without mDNS: {}", err);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    *active = Some(Lan { mdns });
+    drop(active);
+
+    log::info!("LAN mode on; reachable at {:?} port {}", lan_addresses(), LOCAL_SERVER_PORT);
+    host.publish("lan-urls", serde_json::json!(lan_urls(state)));
+    Ok(())
+}
+
+pub fn stop(host: &dyn Host, state: &AppState) {
+    let Some(lan) = ACTIVE.lock().unwrap().take() else {
+        return;
+    };
+    if let Some((daemon, fullname)) = lan.mdns {
+        // Wait briefly so the goodbye packets go out before the daemon exits
+        if let Ok(status) = daemon.unregister(&fullname) {
+            let _ = status.recv_timeout(Duration::from_secs(1));
+        }
+        let _ = daemon.shutdown();
+    }
+    // The token stays while a tunnel is up or starting
+    if state.tunnel_process.try_lock().is_ok_and(|tunnel| tunnel.is_none()) {
+        clear_tunnel_token(state);
+    }
+    log::info!("LAN mode off");
+    host.publish("lan-urls", serde_json::json!([]));
+}
+
+// Used when settings change. The listen address is fixed when the server
+// spawns, so a running bundled server is restarted and LAN mode is only
+// announced once it is back up on all interfaces.
+pub fn apply(host: &dyn Host, lan: &LanSettings) {
+    if lan.enabled == is_active() {
+        return;
+    }
+    let enabled = lan.enabled;
+    let host = host.clone_host();
+    thread::spawn(move || {
+        let state = host.app_state();
+        if !enabled {
+            stop(host.as_ref(), state);
+        }
+
+        let running = !settings::get().server.external && state.server_process.lock().unwrap().is_some();
+        if running {
+            log::info!("Restarting the server to {} LAN access", if enabled { "allow" } else { "remove" });
+            stop_server_internal(state);
+            if let Err(err) = start_server_internal(host.as_ref(), state) {
+                log::error!("Failed to restart the server for LAN mode: {}", err);
+                host.publish("server-status", format!("error: {}", err).into());
+                return;
+            }
+            if !wait_for_server_health(10, 500) {
+                log::error!("Server did not come back after the LAN mode change");
+                return;
+            }
+        }
+
+        // A stopped server picks up the listen address when it next starts
+        if enabled {
+            if let Err(err) = start(host.as_ref(), state) {
+                log::warn!("Failed to start LAN mode: {}", err);
+            }
+        }
+    });
+}
+
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub fn get_lan_urls(state: tauri::State<AppState>) -> Vec<String> {
+    lan_urls(&state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_ipv4_network_addresses() {
+        for ip in ["192.168.1.20", "10.0.0.5", "172.16.3.4", "203.0.113.7"] {
+            assert!(is_lan_address(&ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn skips_loopback_link_local_unspecified_and_ipv6() {
+        for ip in ["127.0.0.1", "169.254.10.1", "0.0.0.0", "::1", "fe80::1", "2001:db8::1", "fd00::1", "::"] {
+            assert!(!is_lan_address(&ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
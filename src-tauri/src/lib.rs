@@ -3,55 +3,52 @@ use std::sync::{Arc, Mutex, mpsc};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::io::{BufRead, BufReader};
 use std::thread;
-use std::net::TcpStream;
-use std::time::Duration;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tauri::{
-    AppHandle, Manager, Runtime,
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter,
-};
 use regex::Regex;
-use tauri_plugin_updater::UpdaterExt;
-use serde::Serialize;
-use tauri_plugin_dialog::{Dialog, FileDialogBuilder};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Deserialize;
+use settings::TunnelStartup;
+
+#[cfg(feature = "gui")]
+mod app;
+mod autostart;
+mod clipboard;
+#[cfg(unix)]
+mod control;
+mod daemon;
+#[cfg(feature = "gui")]
+mod diagnostics;
+mod lan;
+mod logs;
+mod monitor;
+#[cfg(feature = "gui")]
+mod notifications;
+mod qr;
+mod redact;
+#[cfg(unix)]
+mod rpc;
+mod secrets;
+mod settings;
+#[cfg(feature = "gui")]
+mod tray;
+#[cfg(feature = "gui")]
+mod updates;
 
 // ...
 
-#[tauri::command]
-async fn request_folder_access<R: Runtime>(
-    app: AppHandle<R>,
-    _path: String,
-) -> Result<Option<String>, String> {
-    let (tx, rx) = std::sync::mpsc::channel();
-    let dialog_plugin_state = app.state::<Dialog<R>>();
-    FileDialogBuilder::new(dialog_plugin_state.inner().clone())
-        .pick_folder(move |path| {
-            tx.send(path).unwrap();
-        });
-
-    // This will block until the user has selected a folder or cancelled the dialog
-    match rx.recv().unwrap() {
-        Some(path) => {
-            let path_str = path.to_string();
-            log::info!("User granted access to folder: {}", path_str);
-            Ok(Some(path_str))
-        }
-        None => {
-            log::info!("User cancelled folder picker");
-            Ok(None)
-        }
-    }
-}
-
 // Global state for managing processes
 struct AppState {
     server_process: Mutex<Option<Child>>,
     tunnel_process: Mutex<Option<Child>>,
     sidecar_process: Mutex<Option<Child>>,
     tunnel_url: Arc<Mutex<Option<String>>>,
+    // Access token for the current tunnel session; see mint_tunnel_token
+    tunnel_token: Mutex<Option<String>>,
+    // When the current tunnel connected; the idle stop counts from here
+    tunnel_started: Mutex<Option<Instant>>,
 }
 
 impl Default for AppState {
@@ -61,10 +58,32 @@ impl Default for AppState {
             tunnel_process: Mutex::new(None),
             sidecar_process: Mutex::new(None),
             tunnel_url: Arc::new(Mutex::new(None)),
+            tunnel_token: Mutex::new(None),
+            tunnel_started: Mutex::new(None),
         }
     }
 }
 
+type EventHandler = Box<dyn Fn(serde_json::Value) + Send + Sync>;
+
+// What process supervision needs from whoever runs it: the Tauri app or the
+// headless daemon
+trait Host: Send + Sync {
+    fn resource_dir(&self) -> Option<PathBuf>;
+    fn publish(&self, event: &str, payload: serde_json::Value);
+    fn clone_host(&self) -> Arc<dyn Host>;
+    fn app_state(&self) -> &AppState;
+    fn shutdown(&self);
+    // Receive an event as it is published; returns an id for `unsubscribe`
+    fn subscribe(&self, event: &str, handler: EventHandler) -> u32;
+    fn unsubscribe(&self, id: u32);
+    // Only the desktop app has one; updates, tray and diagnostics need it
+    #[cfg(feature = "gui")]
+    fn app_handle(&self) -> Option<tauri::AppHandle> {
+        None
+    }
+}
+
 fn resolve_lsof_path() -> Option<PathBuf> {
     let candidates = ["/usr/sbin/lsof", "/usr/bin/lsof"];
     for path in candidates {
@@ -109,24 +128,64 @@ fn kill_port_listener(port: u16) {
     }
 }
 
+// Where the bundled server listens
+const LOCAL_SERVER_HOST: &str = "127.0.0.1";
+const LOCAL_SERVER_PORT: u16 = 3456;
+
+// Host and port of the server the app talks to: the bundled one, or the
+// configured one in external server mode
+fn server_address() -> (String, u16) {
+    let server = settings::get().server;
+    if server.external {
+        (server.external_host, server.external_port)
+    } else {
+        (LOCAL_SERVER_HOST.to_string(), LOCAL_SERVER_PORT)
+    }
+}
+
+// host:port with IPv6 literals bracketed, for URLs and Host headers
+fn server_authority() -> String {
+    let (host, port) = server_address();
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+fn server_url() -> String {
+    format!("http://{}", server_authority())
+}
+
+// Hostnames (a LAN machine, a container name) can resolve to several addresses
+fn connect_server(timeout: Duration) -> std::io::Result<TcpStream> {
+    let (host, port) = server_address();
+    let mut last_error = std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} did not resolve", host));
+    for addr in (host.as_str(), port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_error = err,
+        }
+    }
+    Err(last_error)
+}
+
 // Check if server is healthy by polling the /health endpoint
 fn wait_for_server_health(max_attempts: u32, delay_ms: u64) -> bool {
     use std::io::{Read, Write};
 
+    let authority = server_authority();
     for attempt in 1..=max_attempts {
         log::info!("Health check attempt {}/{}", attempt, max_attempts);
 
         // Try to connect and send HTTP request
-        match TcpStream::connect_timeout(
-            &"127.0.0.1:3456".parse().unwrap(),
-            Duration::from_millis(1000),
-        ) {
+        match connect_server(Duration::from_millis(1000)) {
             Ok(mut stream) => {
                 // Set read timeout
                 let _ = stream.set_read_timeout(Some(Duration::from_millis(2000)));
 
                 // Send HTTP GET request
-                let request = "GET /health HTTP/1.1\r\nHost: localhost:3456\r\nConnection: close\r\n\r\n";
+                let request = format!("GET /health HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", authority);
                 if stream.write_all(request.as_bytes()).is_ok() {
                     let mut response = String::new();
                     if stream.read_to_string(&mut response).is_ok() {
@@ -148,78 +207,41 @@ fn wait_for_server_health(max_attempts: u32, delay_ms: u64) -> bool {
         }
     }
 
-    log::warn!("Server health check failed after {} attempts", max_attempts);
+    log::warn!("Server health check at {} failed after {} attempts", authority, max_attempts);
     false
 }
 
-// Tauri commands exposed to frontend
-#[tauri::command]
-fn get_tunnel_url(state: tauri::State<AppState>) -> Option<String> {
-    state.tunnel_url.lock().unwrap().clone()
-}
-
-#[tauri::command]
-fn is_server_running(state: tauri::State<AppState>) -> bool {
-    state.server_process.lock().unwrap().is_some()
-}
-
-#[tauri::command]
-fn restart_server(app: AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
-    stop_server_internal(&state);
-    start_server_internal(&app, &state).map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-fn stop_server(state: tauri::State<AppState>) -> Result<(), String> {
-    stop_server_internal(&state);
-    Ok(())
-}
-
-#[tauri::command]
-fn start_tunnel(app: AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
-    start_tunnel_internal(&app, &state).map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-fn stop_tunnel(state: tauri::State<AppState>) -> Result<(), String> {
-    stop_tunnel_internal(&state);
-    Ok(())
-}
+// GET a path on the server and return the response body
+fn server_get(path: &str) -> Option<String> {
+    use std::io::{Read, Write};
 
-#[tauri::command]
-fn restart_tunnel(app: AppHandle, state: tauri::State<AppState>) -> Result<(), String> {
-    stop_tunnel_internal(&state);
-    start_tunnel_internal(&app, &state).map_err(|e| e.to_string())
-}
+    let mut stream = connect_server(Duration::from_millis(1000)).ok()?;
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(2000)));
 
-#[tauri::command]
-fn copy_tunnel_url(state: tauri::State<AppState>) -> Result<String, String> {
-    state.tunnel_url.lock().unwrap()
-        .clone()
-        .ok_or_else(|| "No tunnel URL available".to_string())
-}
+    let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", path, server_authority());
+    stream.write_all(request.as_bytes()).ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
 
-// Update info structure for frontend
-#[derive(Clone, Serialize)]
-pub struct UpdateInfo {
-    pub version: String,
-    pub current_version: String,
-    pub body: Option<String>,
+    if !response.starts_with("HTTP/1.1 200") {
+        return None;
+    }
+    response.split_once("\r\n\r\n").map(|(_, body)| body.to_string())
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 fn get_app_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 fn is_claude_code_installed() -> bool {
     let home = std::env::var("HOME").unwrap_or_default();
     let settings_path = std::path::Path::new(&home).join(".claude").join("settings.json");
     settings_path.exists()
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 fn is_claude_mem_installed() -> bool {
     let home = std::env::var("HOME").unwrap_or_default();
     let worker_path = std::path::Path::new(&home)
@@ -230,70 +252,6 @@ fn is_claude_mem_installed() -> bool {
     worker_path.exists()
 }
 
-#[tauri::command]
-async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
-    let updater = app.updater().map_err(|e| e.to_string())?;
-
-    match updater.check().await {
-        Ok(Some(update)) => {
-            log::info!("Update available: {}", update.version);
-            Ok(Some(UpdateInfo {
-                version: update.version.clone(),
-                current_version: env!("CARGO_PKG_VERSION").to_string(),
-                body: update.body.clone(),
-            }))
-        }
-        Ok(None) => {
-            log::info!("No update available");
-            Ok(None)
-        }
-        Err(e) => {
-            log::error!("Failed to check for updates: {}", e);
-            Err(e.to_string())
-        }
-    }
-}
-
-#[tauri::command]
-async fn install_update(app: AppHandle) -> Result<(), String> {
-    let updater = app.updater().map_err(|e| e.to_string())?;
-
-    match updater.check().await {
-        Ok(Some(update)) => {
-            log::info!("Downloading update: {}", update.version);
-
-            // Emit download progress events
-            let app_handle = app.clone();
-            let mut downloaded: usize = 0;
-
-            update.download_and_install(
-                |chunk_length, content_length| {
-                    downloaded += chunk_length;
-                    let progress = if let Some(total) = content_length {
-                        (downloaded as f64 / total as f64 * 100.0) as u32
-                    } else {
-                        0
-                    };
-                    let _ = app_handle.emit("update-download-progress", progress);
-                },
-                || {
-                    log::info!("Download finished, installing...");
-                    let _ = app_handle.emit("update-installing", true);
-                },
-            ).await.map_err(|e| e.to_string())?;
-
-            log::info!("Update installed, restarting...");
-            app.restart();
-        }
-        Ok(None) => {
-            Err("No update available".to_string())
-        }
-        Err(e) => {
-            Err(e.to_string())
-        }
-    }
-}
-
 // Find the project root directory
 fn find_project_root() -> Option<std::path::PathBuf> {
     // In dev mode, CARGO_MANIFEST_DIR points to src-tauri
@@ -336,8 +294,33 @@ fn find_project_root() -> Option<std::path::PathBuf> {
     None
 }
 
+// Server environment from settings (env vars of the same names override them)
+fn apply_server_env(cmd: &mut Command) {
+    let server = settings::get().server;
+    let log_dir = default_log_dir();
+    let _ = fs::create_dir_all(&log_dir);
+    cmd
+        .env("PTY_BACKEND", &server.pty_backend)
+        .env("PTY_SIDECAR_URL", &server.pty_sidecar_url)
+        .env("SERVER_LOG", server.server_log.unwrap_or_else(|| log_dir.join("server.log")))
+        .env("PTY_SIDECAR_LOG", server.pty_sidecar_log.unwrap_or_else(|| log_dir.join("pty-sidecar.log")))
+        // Rewritten with a fresh token whenever a tunnel starts
+        .env("MT_TUNNEL_TOKEN_FILE", tunnel_token_path());
+    // LAN mode: reachable from other machines, which the token gate covers
+    if settings::get().lan.enabled {
+        cmd.env("MT_BIND_HOST", "0.0.0.0");
+    }
+}
+
 // Internal functions
-fn start_server_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+fn start_server_internal(app: &dyn Host, state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+    // Restarts from the tray, CLI, monitor or updater leave an external server alone
+    if settings::get().server.external {
+        log::info!("External server mode; not starting the bundled server");
+        app.publish("server-status", "external".into());
+        return Ok(());
+    }
+
     let mut server = state.server_process.lock().unwrap();
 
     if server.is_some() {
@@ -346,7 +329,7 @@ fn start_server_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
     }
 
     // Emit starting status
-    let _ = app.emit("server-status", "starting");
+    app.publish("server-status", "starting".into());
 
     // Check if we're running in production (bundled app) or development
     let is_production = !cfg!(debug_assertions);
@@ -360,8 +343,8 @@ fn start_server_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
         }
 
         // Production mode: Use bundled Node.js and server
-        let resource_dir = app.path().resource_dir()
-            .map_err(|e| format!("Could not get resource dir: {}", e))?;
+        let resource_dir = app.resource_dir()
+            .ok_or("Could not get resource dir")?;
 
         let node_path = resource_dir.join("bin").join("node");
         let server_path = resource_dir.join("server").join("server.js");
@@ -389,7 +372,7 @@ fn start_server_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
         cmd
             .arg(&server_path)
             .env("NODE_ENV", "production")
-            .env("PORT", "3456")
+            .env("PORT", LOCAL_SERVER_PORT.to_string())
             .env("NODE_PTY_BINARY", &pty_binary_path)
             .env_remove("npm_config_prefix")
             .env_remove("NPM_CONFIG_PREFIX")
@@ -400,22 +383,7 @@ fn start_server_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        if std::env::var("PTY_BACKEND").is_err() {
-            cmd.env("PTY_BACKEND", "sidecar");
-        }
-        if std::env::var("PTY_SIDECAR_URL").is_err() {
-            cmd.env("PTY_SIDECAR_URL", "http://127.0.0.1:3457");
-        }
-        if std::env::var("SERVER_LOG").is_err() {
-            let log_dir = default_log_dir();
-            let _ = fs::create_dir_all(&log_dir);
-            cmd.env("SERVER_LOG", log_dir.join("server.log"));
-        }
-        if std::env::var("PTY_SIDECAR_LOG").is_err() {
-            let log_dir = default_log_dir();
-            let _ = fs::create_dir_all(&log_dir);
-            cmd.env("PTY_SIDECAR_LOG", log_dir.join("pty-sidecar.log"));
-        }
+        apply_server_env(&mut cmd);
 
         cmd.spawn()?
     } else {
@@ -439,22 +407,7 @@ fn start_server_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        if std::env::var("PTY_BACKEND").is_err() {
-            cmd.env("PTY_BACKEND", "sidecar");
-        }
-        if std::env::var("PTY_SIDECAR_URL").is_err() {
-            cmd.env("PTY_SIDECAR_URL", "http://127.0.0.1:3457");
-        }
-        if std::env::var("SERVER_LOG").is_err() {
-            let log_dir = default_log_dir();
-            let _ = fs::create_dir_all(&log_dir);
-            cmd.env("SERVER_LOG", log_dir.join("server.log"));
-        }
-        if std::env::var("PTY_SIDECAR_LOG").is_err() {
-            let log_dir = default_log_dir();
-            let _ = fs::create_dir_all(&log_dir);
-            cmd.env("PTY_SIDECAR_LOG", log_dir.join("pty-sidecar.log"));
-        }
+        apply_server_env(&mut cmd);
 
         cmd.spawn()?
     };
@@ -462,7 +415,7 @@ fn start_server_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
     *server = Some(child);
 
     // Emit event to frontend
-    let _ = app.emit("server-status", "running");
+    app.publish("server-status", "running".into());
     log::info!("Server started successfully");
 
     Ok(())
@@ -492,7 +445,15 @@ fn stop_server_internal(state: &AppState) {
     stop_sidecar_internal(state);
 }
 
-fn start_sidecar_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+fn sidecar_log_path() -> PathBuf {
+    settings::get().server.pty_sidecar_log.unwrap_or_else(|| {
+        let log_dir = default_log_dir();
+        let _ = fs::create_dir_all(&log_dir);
+        log_dir.join("pty-sidecar.log")
+    })
+}
+
+fn start_sidecar_internal(app: &dyn Host, state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
     let mut sidecar = state.sidecar_process.lock().unwrap();
 
     if sidecar.is_some() {
@@ -509,9 +470,7 @@ fn start_sidecar_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<d
             return Err(format!("PTY sidecar script not found at {:?}", sidecar_path).into());
         }
 
-        let log_dir = default_log_dir();
-        let _ = fs::create_dir_all(&log_dir);
-        let log_path = log_dir.join("pty-sidecar.log");
+        let log_path = sidecar_log_path();
         log::info!("Starting PTY sidecar in development (log: {:?})", log_path);
 
         let bundled_node = project_root.join("src-tauri").join("bin").join("node");
@@ -538,8 +497,8 @@ fn start_sidecar_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<d
             .stderr(Stdio::piped())
             .spawn()?
     } else {
-        let resource_dir = app.path().resource_dir()
-            .map_err(|e| format!("Could not get resource dir: {}", e))?;
+        let resource_dir = app.resource_dir()
+            .ok_or("Could not get resource dir")?;
 
         let node_path = resource_dir.join("bin").join("node");
         let sidecar_path = resource_dir.join("pty-sidecar.cjs");
@@ -552,9 +511,7 @@ fn start_sidecar_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<d
             return Err(format!("PTY sidecar script not found at {:?}", sidecar_path).into());
         }
 
-        let log_dir = default_log_dir();
-        let _ = fs::create_dir_all(&log_dir);
-        let log_path = log_dir.join("pty-sidecar.log");
+        let log_path = sidecar_log_path();
 
         log::info!("Starting PTY sidecar with Node.js at {:?} (log: {:?})", node_path, log_path);
 
@@ -601,7 +558,112 @@ fn default_log_dir() -> PathBuf {
         .join("Terminal Tunnel")
 }
 
-fn start_tunnel_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+// Mirrors Tauri's app_config_dir so code without an AppHandle can use it
+fn default_config_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let base = if cfg!(target_os = "macos") {
+        PathBuf::from(home).join("Library").join("Application Support")
+    } else {
+        std::env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(home).join(".config"))
+    };
+    base.join("com.terminaltunnel.app")
+}
+
+// Locate cloudflared - try multiple locations
+fn resolve_cloudflared_path(app: &dyn Host) -> String {
+    // 1. Try bundled binary in resource dir
+    if let Some(resource_dir) = app.resource_dir() {
+        let bundled = resource_dir.join("bin").join("cloudflared");
+        if bundled.exists() {
+            log::info!("Using bundled cloudflared: {:?}", bundled);
+            bundled.to_string_lossy().to_string()
+        } else {
+            log::info!("Bundled cloudflared not found at {:?}", bundled);
+            "cloudflared".to_string()
+        }
+    } else {
+        // 2. Try project bin directory in dev mode
+        if let Some(project_root) = find_project_root() {
+            let dev_bundled = project_root.join("src-tauri").join("bin").join("cloudflared");
+            if dev_bundled.exists() {
+                log::info!("Using dev cloudflared: {:?}", dev_bundled);
+                dev_bundled.to_string_lossy().to_string()
+            } else {
+                log::info!("Dev cloudflared not found at {:?}, using PATH", dev_bundled);
+                "cloudflared".to_string()
+            }
+        } else {
+            // 3. Fall back to PATH
+            "cloudflared".to_string()
+        }
+    }
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+fn get_log_level() -> String {
+    log::max_level().to_string().to_lowercase()
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+fn set_log_level(level: String) -> Result<(), String> {
+    let filter: log::LevelFilter = level
+        .parse()
+        .map_err(|_| format!("Invalid log level: {}", level))?;
+    settings::modify(|settings| settings.logging.level = filter.to_string().to_lowercase())?;
+    log::set_max_level(filter);
+    log::info!("Log level set to {}", filter);
+    Ok(())
+}
+
+// The server reads the current token from this file (MT_TUNNEL_TOKEN_FILE) and
+// turns away tunnel requests that have not presented it
+fn tunnel_token_path() -> PathBuf {
+    default_config_dir().join("run").join("tunnel-token")
+}
+
+// Fresh 256-bit token per tunnel session, so restarting the tunnel rotates it
+fn mint_tunnel_token(state: &AppState) -> Result<(), String> {
+    let token = URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>());
+    secrets::write_private(&tunnel_token_path(), token.as_bytes())?;
+    *state.tunnel_token.lock().unwrap() = Some(token);
+    log::info!("Rotated tunnel access token");
+    Ok(())
+}
+
+// Servers that predate the flag report nothing, which counts as no gate
+fn server_enforces_tunnel_token() -> bool {
+    server_get("/health")
+        .and_then(|body| serde_json::from_str::<serde_json::Value>(&body).ok())
+        .is_some_and(|health| health["tunnelGate"] == true)
+}
+
+fn clear_tunnel_token(state: &AppState) {
+    // LAN mode keeps gating other machines on the network after the tunnel stops
+    if lan::is_active() {
+        return;
+    }
+    *state.tunnel_token.lock().unwrap() = None;
+    let _ = fs::remove_file(tunnel_token_path());
+}
+
+fn copy_share_url(app: &dyn Host, state: &AppState, clear_after: Option<u64>) -> Result<String, String> {
+    let url = share_url(state).ok_or_else(|| "No tunnel URL available".to_string())?;
+    clipboard::copy(app, &url, clear_after.or_else(clipboard::default_clear_after))?;
+    log::info!("Copied tunnel URL to clipboard");
+    Ok(url)
+}
+
+fn share_url(state: &AppState) -> Option<String> {
+    let url = state.tunnel_url.lock().unwrap().clone()?;
+    match state.tunnel_token.lock().unwrap().as_deref() {
+        Some(token) => Some(format!("{}/?token={}", url, token)),
+        None => Some(url),
+    }
+}
+
+fn start_tunnel_internal(app: &dyn Host, state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
     let mut tunnel = state.tunnel_process.lock().unwrap();
 
     if tunnel.is_some() {
@@ -609,45 +671,37 @@ fn start_tunnel_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
         return Ok(()); // Already running
     }
 
+    if settings::get().tunnel.startup == TunnelStartup::Never {
+        return Err("Tunnel is disabled (tunnel.startup is \"never\")".into());
+    }
+
+    // The bundled server always gets MT_TUNNEL_TOKEN_FILE; an external one may
+    // not, and tokenized links would then only look protected
+    if settings::get().server.external && !server_enforces_tunnel_token() {
+        log::error!("External server at {} does not enforce the tunnel access token", server_url());
+        return Err(format!(
+            "External server at {} does not enforce the tunnel access token; start it with MT_TUNNEL_TOKEN_FILE={}",
+            server_url(),
+            tunnel_token_path().display()
+        ).into());
+    }
+
     // Emit starting status
-    let _ = app.emit("tunnel-status", "starting");
-
-    // Check if cloudflared is available - try multiple locations
-    let cloudflared_path = {
-        // 1. Try bundled binary in resource dir
-        if let Ok(resource_dir) = app.path().resource_dir() {
-            let bundled = resource_dir.join("bin").join("cloudflared");
-            if bundled.exists() {
-                log::info!("Using bundled cloudflared: {:?}", bundled);
-                bundled.to_string_lossy().to_string()
-            } else {
-                log::info!("Bundled cloudflared not found at {:?}", bundled);
-                "cloudflared".to_string()
-            }
-        } else {
-            // 2. Try project bin directory in dev mode
-            if let Some(project_root) = find_project_root() {
-                let dev_bundled = project_root.join("src-tauri").join("bin").join("cloudflared");
-                if dev_bundled.exists() {
-                    log::info!("Using dev cloudflared: {:?}", dev_bundled);
-                    dev_bundled.to_string_lossy().to_string()
-                } else {
-                    log::info!("Dev cloudflared not found at {:?}, using PATH", dev_bundled);
-                    "cloudflared".to_string()
-                }
-            } else {
-                // 3. Fall back to PATH
-                "cloudflared".to_string()
-            }
-        }
-    };
+    app.publish("tunnel-status", "starting".into());
+
+    // The token must be in place before cloudflared accepts traffic
+    mint_tunnel_token(state)?;
+    if lan::is_active() {
+        app.publish("lan-urls", serde_json::json!(lan::lan_urls(state)));
+    }
+
+    let cloudflared_path = resolve_cloudflared_path(app);
 
     log::info!("Starting tunnel with: {}", cloudflared_path);
 
-    // Tunnel to the server in both dev and prod.
+    // Tunnel to the server in both dev and prod, or to the external server.
     // In dev, the server proxies the UI to Vite for remote access stability.
-    let tunnel_port: u16 = 3456;
-    let tunnel_url = format!("http://127.0.0.1:{}", tunnel_port);
+    let tunnel_url = server_url();
 
     log::info!("Tunnel pointing to: {}", tunnel_url);
 
@@ -667,16 +721,16 @@ fn start_tunnel_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
 
     let stdout = child.stdout.take().expect("Failed to capture stdout");
     let stderr = child.stderr.take().expect("Failed to capture stderr");
-    let app_handle_clone = app.clone();
+    let app_handle_clone = app.clone_host();
     let tunnel_url_state = Arc::clone(&state.tunnel_url);
     let (ready_tx, ready_rx) = mpsc::channel::<bool>();
     let found = Arc::new(AtomicBool::new(false));
     let url_regex = Arc::new(Regex::new(r"https://[a-zA-Z0-9-]+\.trycloudflare\.com").unwrap());
 
-    let spawn_reader = |reader: Box<dyn BufRead + Send>, tx: mpsc::Sender<bool>, app_handle: AppHandle, state: Arc<Mutex<Option<String>>>, found: Arc<AtomicBool>, url_regex: Arc<Regex>| {
+    let spawn_reader = |reader: Box<dyn BufRead + Send>, tx: mpsc::Sender<bool>, app_handle: Arc<dyn Host>, state: Arc<Mutex<Option<String>>>, found: Arc<AtomicBool>, url_regex: Arc<Regex>| {
         thread::spawn(move || {
             for line in reader.lines().map_while(Result::ok) {
-                log::info!("cloudflared: {}", line);
+                log::info!(target: "cloudflared", "{}", line);
 
                 if !found.load(Ordering::Relaxed) {
                     if let Some(captures) = url_regex.find(&line) {
@@ -687,8 +741,8 @@ fn start_tunnel_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
                             *guard = Some(url_str.clone());
                         }
 
-                        let _ = app_handle.emit("tunnel-url", url_str.clone());
-                        let _ = app_handle.emit("tunnel-status", "connected");
+                        app_handle.publish("tunnel-url", url_str.clone().into());
+                        app_handle.publish("tunnel-status", "connected".into());
                         found.store(true, Ordering::Relaxed);
                         let _ = tx.send(true);
                     }
@@ -696,7 +750,7 @@ fn start_tunnel_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
 
                 if line.contains("QuickTunnel") {
                     log::warn!("QuickTunnel warning: {}", line);
-                    let _ = app_handle.emit("tunnel-status", format!("error: {}", line));
+                    app_handle.publish("tunnel-status", format!("error: {}", redact::redact(&line)).into());
                 }
             }
         })
@@ -721,13 +775,15 @@ fn start_tunnel_internal(app: &AppHandle, state: &AppState) -> Result<(), Box<dy
 
     match ready_rx.recv_timeout(Duration::from_secs(40)) {
         Ok(true) => {
+            *state.tunnel_started.lock().unwrap() = Some(Instant::now());
             *tunnel = Some(child);
             Ok(())
         }
         _ => {
             let _ = child.kill();
             let _ = child.wait();
-            let _ = app.emit("tunnel-status", "error: cloudflared failed to establish a tunnel");
+            clear_tunnel_token(state);
+            app.publish("tunnel-status", "error: cloudflared failed to establish a tunnel".into());
             Err("cloudflared failed to establish a tunnel".into())
         }
     }
@@ -756,235 +812,204 @@ fn stop_tunnel_internal(state: &AppState) {
     if let Ok(mut url) = state.tunnel_url.lock() {
         *url = None;
     }
+    *state.tunnel_started.lock().unwrap() = None;
+    clear_tunnel_token(state);
 }
 
-// Tray menu creation - commented out as menubar icon is not needed
-// Keep function for potential future use
-/*
-fn create_tray_menu<R: Runtime>(app: &AppHandle<R>) -> Result<Menu<R>, Box<dyn std::error::Error>> {
-    let show = MenuItem::with_id(app, "show", "Open Dashboard", true, None::<&str>)?;
-    let copy_url = MenuItem::with_id(app, "copy_url", "Copy Tunnel URL", true, None::<&str>)?;
-    let separator1 = MenuItem::with_id(app, "sep1", "─────────────", false, None::<&str>)?;
-    let restart_server = MenuItem::with_id(app, "restart_server", "Restart Server", true, None::<&str>)?;
-    let restart_tunnel = MenuItem::with_id(app, "restart_tunnel", "Restart Tunnel", true, None::<&str>)?;
-    let separator2 = MenuItem::with_id(app, "sep2", "─────────────", false, None::<&str>)?;
-    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-
-    let menu = Menu::with_items(app, &[
-        &show,
-        &copy_url,
-        &separator1,
-        &restart_server,
-        &restart_tunnel,
-        &separator2,
-        &quit,
-    ])?;
-
-    Ok(menu)
-}
-*/
-
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_process::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_updater::Builder::new().build())
-        .manage(AppState::default())
-        .invoke_handler(tauri::generate_handler![
-            get_tunnel_url,
-            is_server_running,
-            restart_server,
-            stop_server,
-            start_tunnel,
-            stop_tunnel,
-            restart_tunnel,
-            copy_tunnel_url,
-            get_app_version,
-            check_for_updates,
-            install_update,
-            is_claude_code_installed,
-            is_claude_mem_installed,
-            request_folder_access,
-        ])
-        .setup(|app| {
-            // Setup logging in debug mode
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
+const PROCESS_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+// A server that crashes again this soon after a restart is left stopped
+const CRASH_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+// Take the child out if it exited without being stopped. A locked mutex means
+// it is being started or stopped right now.
+fn take_exited(process: &Mutex<Option<Child>>) -> Option<std::process::ExitStatus> {
+    let mut guard = process.try_lock().ok()?;
+    match guard.as_mut()?.try_wait() {
+        Ok(Some(status)) => {
+            *guard = None;
+            Some(status)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TunnelActivity {
+    last_tunnel_request: Option<u64>,
+    now: u64,
+}
+
+// Time since the last request that came through the tunnel, or since it
+// connected if none has. None when no tunnel is up or the server can't say.
+fn tunnel_idle_for(state: &AppState) -> Option<Duration> {
+    let started = (*state.tunnel_started.lock().unwrap())?;
+    let activity = server_get("/api/activity")
+        .and_then(|body| serde_json::from_str::<TunnelActivity>(&body).ok())?;
+    let since_connect = started.elapsed();
+    Some(match activity.last_tunnel_request {
+        Some(last) => Duration::from_millis(activity.now.saturating_sub(last)).min(since_connect),
+        None => since_connect,
+    })
+}
+
+// Report a server or tunnel that died on its own; the server is restarted.
+// Also stops a tunnel that has been idle longer than tunnel.idle_stop_minutes.
+fn spawn_process_watch(app: Arc<dyn Host>) {
+    thread::spawn(move || {
+        let mut last_restart: Option<Instant> = None;
+        loop {
+            thread::sleep(PROCESS_WATCH_INTERVAL);
+            let state = app.app_state();
+
+            if let Some(status) = take_exited(&state.tunnel_process) {
+                log::error!("Tunnel exited unexpectedly ({})", status);
+                stop_tunnel_internal(state);
+                app.publish("tunnel-status", format!("error: Tunnel lost ({})", status).into());
             }
 
-            // Tray icon setup - commented out as menubar icon is not needed
-            // Window is still accessible via dock icon
-            /*
-            let menu = create_tray_menu(app.handle())?;
-
-            let _tray = TrayIconBuilder::new()
-                .icon(app.default_window_icon().unwrap().clone())
-                .icon_as_template(true)
-                .menu(&menu)
-                .show_menu_on_left_click(false)
-                .on_menu_event(|app, event| {
-                    match event.id.as_ref() {
-                        "show" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                        "copy_url" => {
-                            let state = app.state::<AppState>();
-                            let url = state.tunnel_url.lock().unwrap().clone();
-                            if let Some(url) = url {
-                                let _ = app.emit("copy-to-clipboard", url);
-                            }
-                        }
-                        "restart_server" => {
-                            let state = app.state::<AppState>();
-                            stop_server_internal(&state);
-                            let _ = start_server_internal(app, &state);
-                        }
-                        "restart_tunnel" => {
-                            let state = app.state::<AppState>();
-                            stop_tunnel_internal(&state);
-                            let _ = start_tunnel_internal(app, &state);
-                        }
-                        "quit" => {
-                            let state = app.state::<AppState>();
-                            stop_server_internal(&state);
-                            stop_tunnel_internal(&state);
-                            stop_sidecar_internal(&state);
-                            app.exit(0);
-                        }
-                        _ => {}
-                    }
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                })
-                .build(app)?;
-            */
-
-            // Start server and tunnel on app launch
-            let app_handle = app.handle().clone();
-
-            // Spawn initialization in background to not block app startup
-            thread::spawn(move || {
-                log::info!("Starting initialization sequence...");
-
-                // Clean up any orphaned processes from previous runs
-                #[cfg(unix)]
-                {
-                    log::info!("Cleaning up orphaned processes...");
-                    // Kill any existing cloudflared tunnel processes (both dev and prod)
-                    let _ = Command::new("pkill")
-                        .args(["-f", "cloudflared tunnel"])
-                        .status();
-
-                    // In development mode, also clean up npm processes
-                    if cfg!(debug_assertions) {
-                        let _ = Command::new("pkill")
-                            .args(["-f", "npm run dev:server"])
-                            .status();
-                        // Only clear the sidecar port here; the dev client manages Vite.
-                        kill_port_listener(3457);
-                    }
-                    // Brief pause to let processes terminate
-                    thread::sleep(std::time::Duration::from_millis(500));
+            let idle_limit = settings::get().tunnel.idle_stop_minutes.map(|minutes| Duration::from_secs(minutes * 60));
+            if let Some(limit) = idle_limit {
+                if tunnel_idle_for(state).is_some_and(|idle| idle >= limit) {
+                    log::info!("No remote requests for {:?}; stopping idle tunnel", limit);
+                    stop_tunnel_internal(state);
+                    app.publish("tunnel-status", "stopped".into());
                 }
+            }
 
-                // Small delay to ensure app is fully initialized
-                thread::sleep(std::time::Duration::from_millis(500));
+            let Some(status) = take_exited(&state.server_process) else {
+                continue;
+            };
+            log::error!("Server exited unexpectedly ({})", status);
+            app.publish("server-status", format!("crashed: {}", status).into());
+            if last_restart.is_some_and(|at| at.elapsed() < CRASH_RESTART_BACKOFF) {
+                log::error!("Server crashed again within {:?}; not restarting", CRASH_RESTART_BACKOFF);
+                stop_server_internal(state);
+                continue;
+            }
+            last_restart = Some(Instant::now());
+            // Also stops the sidecar so it restarts with the server
+            stop_server_internal(state);
+            if let Err(e) = start_server_internal(app.as_ref(), state) {
+                log::error!("Failed to restart server after crash: {}", e);
+                app.publish("server-status", format!("error: {}", e).into());
+            }
+        }
+    });
+}
 
-                let state = app_handle.state::<AppState>();
+// Launch sequence shared by the app and the headless daemon: clean up orphans,
+// start the server, wait for it, then start the tunnel unless tunnel.startup
+// says otherwise. `on_server_ready` runs once the health check settles, before
+// the tunnel starts.
+fn start_services(app: &dyn Host, state: &AppState, on_server_ready: impl FnOnce(bool)) {
+    log::info!("Starting initialization sequence...");
+
+    // Clean up any orphaned processes from previous runs
+    #[cfg(unix)]
+    {
+        log::info!("Cleaning up orphaned processes...");
+        // Kill any existing cloudflared tunnel processes (both dev and prod)
+        let _ = Command::new("pkill")
+            .args(["-f", "cloudflared tunnel"])
+            .status();
+
+        // In development mode, also clean up npm processes
+        if cfg!(debug_assertions) {
+            let _ = Command::new("pkill")
+                .args(["-f", "npm run dev:server"])
+                .status();
+            // Only clear the sidecar port here; the dev client manages Vite.
+            kill_port_listener(3457);
+        }
+        // Brief pause to let processes terminate
+        thread::sleep(std::time::Duration::from_millis(500));
+    }
 
-                let external_server = std::env::var("MT_EXTERNAL_SERVER").ok().as_deref() == Some("1");
+    // Small delay to ensure app is fully initialized
+    thread::sleep(std::time::Duration::from_millis(500));
 
-                if external_server {
-                    log::info!("External server enabled; skipping internal server start");
-                } else {
-                    // Start server
-                    log::info!("Starting server...");
-                    match start_server_internal(&app_handle, &state) {
-                        Ok(_) => log::info!("Server process spawned"),
-                        Err(e) => {
-                            log::error!("Failed to start server: {}", e);
-                            let _ = app_handle.emit("server-status", format!("error: {}", e));
-                            return; // Don't continue if server failed to spawn
-                        }
-                    }
-                }
+    // Sample CPU/memory of the managed processes for the resource thresholds
+    monitor::spawn_monitor(app.clone_host());
 
-                // Wait for server to be ready (health check with retries)
-                // 10 attempts, 500ms between each = up to 5 seconds total
-                log::info!("Waiting for server to be ready...");
-                let server_ready = wait_for_server_health(10, 500);
+    let external_server = settings::get().server.external;
 
-                if !server_ready {
-                    log::error!("Server failed to become ready - health check timed out");
-                    let _ = app_handle.emit("server-status", "error: Server failed to start");
-                    // Continue anyway - user may want to retry or the server may still start
-                }
+    if external_server {
+        log::info!("External server enabled at {}; skipping internal server start", server_url());
+        app.publish("server-status", "external".into());
+    } else {
+        // Start server
+        log::info!("Starting server...");
+        match start_server_internal(app, state) {
+            Ok(_) => log::info!("Server process spawned"),
+            Err(e) => {
+                log::error!("Failed to start server: {}", e);
+                app.publish("server-status", format!("error: {}", e).into());
+                return; // Don't continue if server failed to spawn
+            }
+        }
+    }
 
-                 // Navigate webview to the correct frontend URL
-                 // Development: Vite dev server on 5173
-                 // Production: bundled server on 3456 (serves the React app + API)
-                 if let Some(window) = app_handle.get_webview_window("main") {
-                     if cfg!(debug_assertions) {
-                         log::info!("Navigating webview to http://127.0.0.1:3456");
-                         let _ = window.eval("window.location.replace('http://127.0.0.1:3456')");
-                     } else if server_ready {
-                         log::info!("Navigating webview to http://localhost:3456");
-                         let _ = window.eval("window.location.replace('http://localhost:3456')");
-                     }
-                 }
-
-                // Only start tunnel if server is ready
-                if server_ready {
-                    // Start tunnel
-                    log::info!("Starting tunnel...");
-                    match start_tunnel_internal(&app_handle, &state) {
-                        Ok(_) => log::info!("Tunnel started successfully"),
-                        Err(e) => {
-                            log::error!("Failed to start tunnel: {}", e);
-                            let _ = app_handle.emit("tunnel-status", format!("error: {}", e));
-                        }
-                    }
-                } else {
-                    log::warn!("Skipping tunnel start - server not ready");
-                    let _ = app_handle.emit("tunnel-status", "error: Server not ready");
-                }
+    // Wait for server to be ready (health check with retries)
+    // 10 attempts, 500ms between each = up to 5 seconds total
+    log::info!("Waiting for server to be ready...");
+    let server_ready = wait_for_server_health(10, 500);
+
+    if !server_ready {
+        log::error!("Server failed to become ready - health check timed out");
+        app.publish("server-status", "error: Server failed to start".into());
+        // Continue anyway - user may want to retry or the server may still start
+    }
 
-                log::info!("Initialization sequence complete");
-            });
+    on_server_ready(server_ready);
 
-            // Handle window close to hide instead of quit
-            if let Some(window) = app.get_webview_window("main") {
-                let window_clone = window.clone();
-                window.on_window_event(move |event| {
-                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                        api.prevent_close();
-                        let _ = window_clone.hide();
-                    }
-                });
+    if server_ready && settings::get().lan.enabled {
+        if let Err(e) = lan::start(app, state) {
+            log::error!("Failed to start LAN mode: {}", e);
+        }
+    }
+
+    // Only start tunnel if server is ready, and only at launch in "always" mode
+    let startup = settings::get().tunnel.startup;
+    if startup != TunnelStartup::Always {
+        log::info!("Tunnel startup is {:?}; not starting the tunnel at launch", startup);
+        app.publish("tunnel-status", "stopped".into());
+    } else if server_ready {
+        // Start tunnel
+        log::info!("Starting tunnel...");
+        match start_tunnel_internal(app, state) {
+            Ok(_) => log::info!("Tunnel started successfully"),
+            Err(e) => {
+                log::error!("Failed to start tunnel: {}", e);
+                app.publish("tunnel-status", format!("error: {}", e).into());
             }
+        }
+    } else {
+        log::warn!("Skipping tunnel start - server not ready");
+        app.publish("tunnel-status", "error: Server not ready".into());
+    }
 
-            Ok(())
-        })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+    spawn_process_watch(app.clone_host());
+    log::info!("Initialization sequence complete");
+}
+
+#[cfg(feature = "gui")]
+pub use app::run;
+
+// Entry point for the `terminal-tunnel-daemon` binary: the same server, sidecar
+// and tunnel supervision without a window, tray or webview
+pub fn run_daemon() {
+    daemon::run();
+}
+
+// Entry point for the `terminal-tunnel` CLI, which controls a running app or
+// daemon over its local socket
+pub fn run_cli() {
+    #[cfg(unix)]
+    control::run_cli();
+    #[cfg(not(unix))]
+    {
+        eprintln!("The terminal-tunnel CLI is only available on macOS and Linux");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{default_log_dir, redact, Host};
+
+// Log files managed by the app, keyed by the name the frontend uses
+const MANAGED_LOGS: [(&str, &str); 4] = [
+    ("app", "app.log"),
+    ("server", "server.log"),
+    ("sidecar", "pty-sidecar.log"),
+    ("tunnel", "tunnel.log"),
+];
+
+// Upper bound for a single read so a huge log can't stall the webview
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Serialize)]
+pub struct LogFileInfo {
+    pub name: String,
+    pub path: String,
+    pub exists: bool,
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LogChunk {
+    pub content: String,
+    pub offset: u64,
+    pub next_offset: u64,
+    pub size: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LogMatch {
+    pub line_number: usize,
+    pub line: String,
+}
+
+#[derive(Clone, Serialize)]
+struct LogLinePayload {
+    subscription: u32,
+    name: String,
+    line: String,
+}
+
+// Active follow subscriptions; each one owns a polling thread
+static NEXT_FOLLOWER: AtomicU32 = AtomicU32::new(0);
+static FOLLOWERS: Mutex<BTreeMap<u32, Arc<AtomicBool>>> = Mutex::new(BTreeMap::new());
+
+pub fn managed_log_path(name: &str) -> Result<PathBuf, String> {
+    MANAGED_LOGS
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, file_name)| default_log_dir().join(file_name))
+        .ok_or_else(|| format!("Unknown log file: {}", name))
+}
+
+pub fn managed_log_files() -> Vec<LogFileInfo> {
+    MANAGED_LOGS
+        .iter()
+        .map(|(name, file_name)| {
+            let path = default_log_dir().join(file_name);
+            let metadata = fs::metadata(&path).ok();
+            LogFileInfo {
+                name: name.to_string(),
+                path: path.to_string_lossy().to_string(),
+                exists: metadata.is_some(),
+                size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                modified: metadata
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_millis() as u64),
+            }
+        })
+        .collect()
+}
+
+// Read the last `count` lines by scanning backwards from the end of the file
+pub fn tail_lines(path: &Path, count: usize) -> Result<Vec<String>, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let mut window: u64 = 64 * 1024;
+    loop {
+        let start = size.saturating_sub(window);
+        file.seek(SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
+
+        let text = String::from_utf8_lossy(&buffer);
+        let mut lines: Vec<&str> = text.lines().collect();
+        // The first line is likely partial unless we started at the beginning
+        if start > 0 && !lines.is_empty() {
+            lines.remove(0);
+        }
+
+        if lines.len() >= count || start == 0 || window >= MAX_READ_BYTES * 16 {
+            let skip = lines.len().saturating_sub(count);
+            return Ok(lines[skip..].iter().map(|line| line.to_string()).collect());
+        }
+        window *= 4;
+    }
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn list_log_files() -> Vec<LogFileInfo> {
+    managed_log_files()
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn read_log(name: String, offset: Option<u64>, length: Option<u64>) -> Result<LogChunk, String> {
+    let path = managed_log_path(&name)?;
+    let mut file = File::open(&path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let offset = offset.unwrap_or(0).min(size);
+    let length = length.unwrap_or(MAX_READ_BYTES).min(MAX_READ_BYTES);
+
+    file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+    let mut buffer = Vec::new();
+    file.take(length)
+        .read_to_end(&mut buffer)
+        .map_err(|e| e.to_string())?;
+
+    // The server and sidecar write these files themselves, bypassing the
+    // redacting log target, so mask secrets on the way out
+    Ok(LogChunk {
+        content: redact::redact(&String::from_utf8_lossy(&buffer)).into_owned(),
+        offset,
+        next_offset: offset + buffer.len() as u64,
+        size,
+    })
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn tail_log(name: String, lines: Option<usize>) -> Result<Vec<String>, String> {
+    let path = managed_log_path(&name)?;
+    let lines = tail_lines(&path, lines.unwrap_or(200))?;
+    Ok(lines.iter().map(|line| redact::redact(line).into_owned()).collect())
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn search_log(
+    name: String,
+    pattern: String,
+    max_results: Option<usize>,
+) -> Result<Vec<LogMatch>, String> {
+    let path = managed_log_path(&name)?;
+    let regex = Regex::new(&pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+    let max_results = max_results.unwrap_or(500);
+
+    let file = File::open(&path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mut matches = Vec::new();
+    for (index, line) in BufReader::new(file).lines().map_while(Result::ok).enumerate() {
+        // Match the masked line so a pattern can't probe for secrets
+        let line = redact::redact(&line);
+        if regex.is_match(&line) {
+            matches.push(LogMatch {
+                line_number: index + 1,
+                line: line.into_owned(),
+            });
+            if matches.len() >= max_results {
+                break;
+            }
+        }
+    }
+
+    Ok(matches)
+}
+
+// Publish a `log-line` event for every line appended to the file until unfollowed
+pub fn follow(host: Arc<dyn Host>, name: String) -> Result<u32, String> {
+    let path = managed_log_path(&name)?;
+    let id = NEXT_FOLLOWER.fetch_add(1, Ordering::Relaxed) + 1;
+    let stop = Arc::new(AtomicBool::new(false));
+    FOLLOWERS.lock().unwrap().insert(id, Arc::clone(&stop));
+
+    thread::spawn(move || {
+        // Start at the current end; earlier content is available via tail_log
+        let mut position = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let mut pending = String::new();
+
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(FOLLOW_POLL_INTERVAL);
+
+            let size = match fs::metadata(&path) {
+                Ok(metadata) => metadata.len(),
+                Err(_) => continue,
+            };
+
+            // File was truncated or rotated; start over from the beginning
+            if size < position {
+                position = 0;
+                pending.clear();
+            }
+            if size == position {
+                continue;
+            }
+
+            let Ok(mut file) = File::open(&path) else { continue };
+            if file.seek(SeekFrom::Start(position)).is_err() {
+                continue;
+            }
+            let mut buffer = Vec::new();
+            if file.take(size - position).read_to_end(&mut buffer).is_err() {
+                continue;
+            }
+            position += buffer.len() as u64;
+            pending.push_str(&String::from_utf8_lossy(&buffer));
+
+            while let Some(newline) = pending.find('\n') {
+                let line: String = pending.drain(..=newline).collect();
+                let payload = LogLinePayload {
+                    subscription: id,
+                    name: name.clone(),
+                    line: redact::redact(line.trim_end_matches(['\r', '\n'])).into_owned(),
+                };
+                host.publish("log-line", serde_json::to_value(payload).unwrap_or_default());
+            }
+        }
+    });
+
+    Ok(id)
+}
+
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub fn follow_log(app: tauri::AppHandle, name: String) -> Result<u32, String> {
+    follow(app.clone_host(), name)
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn unfollow_log(subscription: u32) -> bool {
+    match FOLLOWERS.lock().unwrap().remove(&subscription) {
+        Some(stop) => {
+            stop.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_lines(name: &str, count: usize) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("tt-{}-{}.log", name, std::process::id()));
+        let content: String = (0..count).map(|n| format!("line {}\n", n)).collect();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn returns_the_last_lines_of_a_small_file() {
+        let path = write_lines("small", 10);
+        assert_eq!(tail_lines(&path, 3).unwrap(), vec!["line 7", "line 8", "line 9"]);
+        assert_eq!(tail_lines(&path, 50).unwrap().len(), 10);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn drops_the_partial_first_line_of_a_window() {
+        // Larger than the first 64 KiB window
+        let path = write_lines("large", 20_000);
+        let lines = tail_lines(&path, 5).unwrap();
+        assert_eq!(lines, vec!["line 19995", "line 19996", "line 19997", "line 19998", "line 19999"]);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn widens_the_window_until_enough_lines_are_read() {
+        let path = write_lines("wide", 20_000);
+        let lines = tail_lines(&path, 12_000).unwrap();
+        assert_eq!(lines.len(), 12_000);
+        assert_eq!(lines[0], "line 8000");
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn handles_empty_and_missing_files() {
+        let path = write_lines("empty", 0);
+        assert!(tail_lines(&path, 10).unwrap().is_empty());
+        let _ = fs::remove_file(&path);
+        assert!(tail_lines(&path, 10).is_err());
+    }
+}
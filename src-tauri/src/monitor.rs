@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::{Child, Command};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+#[cfg(target_os = "linux")]
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    resolve_lsof_path, settings, start_server_internal, start_tunnel_internal, stop_server_internal,
+    stop_tunnel_internal, AppState, Host,
+};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+// Restart only after the threshold has been exceeded this many samples in a row
+const RESTART_AFTER_SAMPLES: u32 = 3;
+
+#[derive(Clone, Serialize)]
+pub struct ProcessStats {
+    pub name: String,
+    pub pid: u32,
+    pub process_count: usize,
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub open_files: Option<u64>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdAction {
+    #[default]
+    Warn,
+    Restart,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ResourceThresholds {
+    pub max_rss_mb: Option<u64>,
+    pub max_cpu_percent: Option<f64>,
+    #[serde(default)]
+    pub action: ThresholdAction,
+}
+
+#[derive(Clone, Serialize)]
+struct ResourceWarning {
+    stats: ProcessStats,
+    reason: String,
+    action: ThresholdAction,
+    consecutive_samples: u32,
+}
+
+struct PsEntry {
+    ppid: u32,
+    // A lifetime average on Linux, so only used elsewhere; see cpu_percent
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    cpu_percent: f64,
+    rss_kb: u64,
+}
+
+// One `ps` snapshot of every process, keyed by PID
+fn process_table() -> HashMap<u32, PsEntry> {
+    let output = match Command::new("ps").args(["-A", "-o", "pid=,ppid=,%cpu=,rss="]).output() {
+        Ok(output) => output,
+        Err(err) => {
+            log::warn!("Failed to run ps: {}", err);
+            return HashMap::new();
+        }
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let cpu_percent = fields.next()?.replace(',', ".").parse().ok()?;
+            let rss_kb = fields.next()?.parse().ok()?;
+            Some((pid, PsEntry { ppid, cpu_percent, rss_kb }))
+        })
+        .collect()
+}
+
+// Linux `ps` reports %CPU as cputime / elapsed over the whole process lifetime,
+// so measure utime + stime deltas between samples instead. USER_HZ is 100 on
+// every mainstream kernel.
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS_PER_SECOND: f64 = 100.0;
+
+// Previous (cpu ticks, time) per PID
+#[cfg(target_os = "linux")]
+static CPU_SAMPLES: Mutex<BTreeMap<u32, (u64, Instant)>> = Mutex::new(BTreeMap::new());
+
+#[cfg(target_os = "linux")]
+fn cpu_ticks(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may hold spaces and parentheses; the rest follows the
+    // last ')', starting with field 3 (state). utime and stime are 14 and 15.
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+// CPU use since the previous sample; a PID seen for the first time counts as 0
+#[cfg(target_os = "linux")]
+fn cpu_percent(pids: &[u32], table: &HashMap<u32, PsEntry>) -> f64 {
+    let now = Instant::now();
+    let mut samples = CPU_SAMPLES.lock().unwrap();
+    samples.retain(|pid, _| table.contains_key(pid));
+    pids.iter()
+        .filter_map(|pid| {
+            let ticks = cpu_ticks(*pid)?;
+            let (last_ticks, last_at) = samples.insert(*pid, (ticks, now))?;
+            let elapsed = now.duration_since(last_at).as_secs_f64();
+            (elapsed > 0.0).then(|| {
+                ticks.saturating_sub(last_ticks) as f64 / CLOCK_TICKS_PER_SECOND / elapsed * 100.0
+            })
+        })
+        .sum()
+}
+
+// macOS and BSD `ps` already report a decaying recent average
+#[cfg(not(target_os = "linux"))]
+fn cpu_percent(pids: &[u32], table: &HashMap<u32, PsEntry>) -> f64 {
+    pids.iter().map(|pid| table[pid].cpu_percent).sum()
+}
+
+fn descendants(table: &HashMap<u32, PsEntry>, root: u32) -> Vec<u32> {
+    let mut pids = vec![root];
+    let mut index = 0;
+    while index < pids.len() {
+        let parent = pids[index];
+        pids.extend(
+            table
+                .iter()
+                .filter(|(pid, entry)| entry.ppid == parent && **pid != parent)
+                .map(|(pid, _)| *pid),
+        );
+        index += 1;
+    }
+    pids
+}
+
+fn open_file_count(pid: u32) -> Option<u64> {
+    // Linux exposes descriptors directly; elsewhere fall back to lsof
+    if let Ok(entries) = fs::read_dir(format!("/proc/{}/fd", pid)) {
+        return Some(entries.count() as u64);
+    }
+
+    let lsof_path = resolve_lsof_path()?;
+    let output = Command::new(lsof_path)
+        .args(["-n", "-P", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let lines = String::from_utf8_lossy(&output.stdout).lines().count() as u64;
+    // First line is the column header
+    Some(lines.saturating_sub(1))
+}
+
+fn child_pid(process: &Mutex<Option<Child>>) -> Option<u32> {
+    process.lock().unwrap().as_ref().map(|child| child.id())
+}
+
+pub fn collect_stats(state: &AppState) -> Vec<ProcessStats> {
+    let roots = [
+        ("server", child_pid(&state.server_process)),
+        ("sidecar", child_pid(&state.sidecar_process)),
+        ("tunnel", child_pid(&state.tunnel_process)),
+    ];
+    if roots.iter().all(|(_, pid)| pid.is_none()) {
+        return Vec::new();
+    }
+
+    let table = process_table();
+    roots
+        .into_iter()
+        .filter_map(|(name, pid)| {
+            let pid = pid?;
+            let pids: Vec<u32> = descendants(&table, pid)
+                .into_iter()
+                .filter(|pid| table.contains_key(pid))
+                .collect();
+            if pids.is_empty() {
+                return None;
+            }
+
+            let open_files = pids
+                .iter()
+                .map(|pid| open_file_count(*pid))
+                .try_fold(0u64, |total, count| count.map(|count| total + count));
+
+            Some(ProcessStats {
+                name: name.to_string(),
+                pid,
+                process_count: pids.len(),
+                cpu_percent: cpu_percent(&pids, &table),
+                rss_bytes: pids.iter().map(|pid| table[pid].rss_kb * 1024).sum(),
+                open_files,
+            })
+        })
+        .collect()
+}
+
+fn threshold_violation(stats: &ProcessStats, thresholds: &ResourceThresholds) -> Option<String> {
+    if let Some(max_rss_mb) = thresholds.max_rss_mb {
+        let rss_mb = stats.rss_bytes / (1024 * 1024);
+        if rss_mb > max_rss_mb {
+            return Some(format!("RSS {} MB exceeds {} MB", rss_mb, max_rss_mb));
+        }
+    }
+    if let Some(max_cpu) = thresholds.max_cpu_percent {
+        if stats.cpu_percent > max_cpu {
+            return Some(format!("CPU {:.1}% exceeds {:.1}%", stats.cpu_percent, max_cpu));
+        }
+    }
+    None
+}
+
+fn restart_process(host: &dyn Host, name: &str) {
+    let state = host.app_state();
+    let result = match name {
+        // The sidecar is owned by the server lifecycle
+        "server" | "sidecar" => {
+            stop_server_internal(state);
+            start_server_internal(host, state)
+        }
+        "tunnel" => {
+            stop_tunnel_internal(state);
+            start_tunnel_internal(host, state)
+        }
+        _ => return,
+    };
+    if let Err(err) = result {
+        log::error!("Failed to restart {} after resource threshold: {}", name, err);
+    }
+}
+
+// Sample periodically and act on the thresholds in settings, in the app and
+// the daemon alike
+pub fn spawn_monitor(host: Arc<dyn Host>) {
+    thread::spawn(move || {
+        let mut strikes: HashMap<String, u32> = HashMap::new();
+
+        loop {
+            thread::sleep(SAMPLE_INTERVAL);
+
+            let thresholds = settings::get().resources;
+            if thresholds.max_rss_mb.is_none() && thresholds.max_cpu_percent.is_none() {
+                strikes.clear();
+                continue;
+            }
+
+            let stats = collect_stats(host.app_state());
+            for stats in stats {
+                let Some(reason) = threshold_violation(&stats, &thresholds) else {
+                    strikes.remove(&stats.name);
+                    continue;
+                };
+
+                let count = strikes.entry(stats.name.clone()).or_insert(0);
+                *count += 1;
+                let consecutive_samples = *count;
+                log::warn!("{} (PID {}) over resource threshold: {}", stats.name, stats.pid, reason);
+
+                let name = stats.name.clone();
+                let warning = ResourceWarning {
+                    stats,
+                    reason,
+                    action: thresholds.action,
+                    consecutive_samples,
+                };
+                host.publish("resource-warning", serde_json::to_value(warning).unwrap_or_default());
+
+                if thresholds.action == ThresholdAction::Restart
+                    && consecutive_samples >= RESTART_AFTER_SAMPLES
+                {
+                    log::warn!("Restarting {} after {} samples over threshold", name, consecutive_samples);
+                    strikes.remove(&name);
+                    restart_process(host.as_ref(), &name);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub fn get_process_stats(state: tauri::State<AppState>) -> Vec<ProcessStats> {
+    collect_stats(&state)
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn get_resource_thresholds() -> ResourceThresholds {
+    settings::get().resources
+}
+
+pub fn validate_thresholds(thresholds: &ResourceThresholds) -> Result<(), String> {
+    if thresholds.max_cpu_percent.is_some_and(|cpu| cpu <= 0.0) {
+        return Err("max_cpu_percent must be positive".to_string());
+    }
+    if thresholds.max_rss_mb == Some(0) {
+        return Err("max_rss_mb must be positive".to_string());
+    }
+    Ok(())
+}
+
+// The monitor reads thresholds from settings on every sample
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn set_resource_thresholds(thresholds: ResourceThresholds) -> Result<(), String> {
+    validate_thresholds(&thresholds)?;
+    settings::modify(|settings| settings.resources = thresholds).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(u32, u32)]) -> HashMap<u32, PsEntry> {
+        entries
+            .iter()
+            .map(|&(pid, ppid)| (pid, PsEntry { ppid, cpu_percent: 0.0, rss_kb: 0 }))
+            .collect()
+    }
+
+    #[test]
+    fn collects_the_whole_process_tree() {
+        let table = table(&[(1, 0), (10, 1), (11, 10), (12, 10), (13, 12), (20, 1), (21, 20)]);
+        let mut pids = descendants(&table, 10);
+        assert_eq!(pids[0], 10);
+        pids.sort_unstable();
+        assert_eq!(pids, vec![10, 11, 12, 13]);
+    }
+
+    #[test]
+    fn a_leaf_or_unknown_root_is_just_itself() {
+        let table = table(&[(1, 0), (10, 1)]);
+        assert_eq!(descendants(&table, 10), vec![10]);
+        assert_eq!(descendants(&table, 99), vec![99]);
+    }
+
+    #[test]
+    fn a_self_parented_process_does_not_loop() {
+        let table = table(&[(0, 0), (1, 0)]);
+        let mut pids = descendants(&table, 0);
+        pids.sort_unstable();
+        assert_eq!(pids, vec![0, 1]);
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Listener, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::settings::{self, NotificationPreferences};
+
+pub struct Notifier {
+    preferences: Mutex<NotificationPreferences>,
+    // Last tunnel status, to tell a lost tunnel from a normal stop
+    tunnel: Mutex<String>,
+    // Set on "crashed:" and kept through the "starting" that follows, so the
+    // next "running" is reported as a restart
+    server_crashed: Mutex<bool>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self {
+            preferences: Mutex::new(load_preferences()),
+            tunnel: Mutex::new("stopped".to_string()),
+            server_crashed: Mutex::new(false),
+        }
+    }
+}
+
+fn load_preferences() -> NotificationPreferences {
+    settings::get().notifications
+}
+
+fn save_preferences(preferences: &NotificationPreferences) -> Result<(), String> {
+    settings::modify(|settings| settings.notifications = preferences.clone()).map(|_| ())
+}
+
+pub fn apply_preferences(notifier: &Notifier, preferences: NotificationPreferences) {
+    *notifier.preferences.lock().unwrap() = preferences;
+}
+
+fn notify(app: &AppHandle, title: &str, body: &str) {
+    if let Err(err) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("Failed to show notification {:?}: {}", title, err);
+    }
+}
+
+fn status_payload(payload: &str) -> String {
+    serde_json::from_str::<String>(payload).unwrap_or_else(|_| payload.to_string())
+}
+
+fn on_tunnel_status(app: &AppHandle, status: String) {
+    let notifier = app.state::<Notifier>();
+    let previous = std::mem::replace(&mut *notifier.tunnel.lock().unwrap(), status.clone());
+    if previous == "connected" && status.starts_with("error:") && notifier.preferences.lock().unwrap().tunnel_lost {
+        let reason = status.trim_start_matches("error:").trim();
+        notify(app, "Tunnel lost", &format!("{}. Restart the tunnel to get a new URL.", reason));
+    }
+}
+
+fn on_server_status(app: &AppHandle, status: String) {
+    let notifier = app.state::<Notifier>();
+    let mut crashed = notifier.server_crashed.lock().unwrap();
+    let enabled = notifier.preferences.lock().unwrap().server_crashed;
+    if status.starts_with("crashed:") {
+        *crashed = true;
+        if enabled {
+            notify(app, "Server crashed", &format!("The terminal server exited ({}).", status.trim_start_matches("crashed:").trim()));
+        }
+    } else if status == "running" {
+        if std::mem::take(&mut *crashed) && enabled {
+            notify(app, "Server restarted", "The terminal server is running again.");
+        }
+    } else if status != "starting" {
+        // Stopped or failed to start: the crash is no longer pending a restart
+        *crashed = false;
+    }
+}
+
+// Listens to the same events the webview gets, so notifications work while the
+// window is hidden
+pub fn setup(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any("tunnel-url", move |event| {
+        if handle.state::<Notifier>().preferences.lock().unwrap().tunnel_url {
+            notify(&handle, "Tunnel ready", &status_payload(event.payload()));
+        }
+    });
+
+    let handle = app.clone();
+    app.listen_any("tunnel-status", move |event| {
+        on_tunnel_status(&handle, status_payload(event.payload()));
+    });
+
+    let handle = app.clone();
+    app.listen_any("server-status", move |event| {
+        on_server_status(&handle, status_payload(event.payload()));
+    });
+
+    let handle = app.clone();
+    app.listen_any("update-available", move |event| {
+        if !handle.state::<Notifier>().preferences.lock().unwrap().update_available {
+            return;
+        }
+        let version = serde_json::from_str::<serde_json::Value>(event.payload())
+            .ok()
+            .and_then(|info| info["version"].as_str().map(str::to_string));
+        let body = match version {
+            Some(version) => format!("Terminal Tunnel {} is available.", version),
+            None => "A new version of Terminal Tunnel is available.".to_string(),
+        };
+        notify(&handle, "Update available", &body);
+    });
+}
+
+#[tauri::command]
+pub fn get_notification_preferences(notifier: tauri::State<Notifier>) -> NotificationPreferences {
+    notifier.preferences.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_notification_preferences(
+    notifier: tauri::State<Notifier>,
+    preferences: NotificationPreferences,
+) -> Result<(), String> {
+    save_preferences(&preferences)?;
+    *notifier.preferences.lock().unwrap() = preferences;
+    Ok(())
+}
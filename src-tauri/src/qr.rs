@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use qrcode::render::svg;
+use qrcode::{Color, QrCode};
+use serde::Serialize;
+
+use crate::{share_url, AppState, Host};
+
+// PNG pixels per module, plus the standard four-module quiet zone
+const PNG_MODULE_SIZE: usize = 8;
+const QUIET_ZONE: usize = 4;
+const SVG_MIN_SIZE: u32 = 256;
+
+#[derive(Clone, Serialize)]
+pub struct TunnelQr {
+    // What the code encodes
+    pub url: String,
+    pub svg: String,
+    // data:image/png;base64,...
+    pub png: String,
+}
+
+// Last rendered codes, rebuilt when the tunnel URL changes
+static CACHE: Mutex<Vec<TunnelQr>> = Mutex::new(Vec::new());
+
+fn render_png(code: &QrCode) -> Result<Vec<u8>, String> {
+    let width = code.width();
+    let colors = code.to_colors();
+    let size = (width + 2 * QUIET_ZONE) * PNG_MODULE_SIZE;
+
+    let mut pixels = vec![0xFFu8; size * size];
+    for (index, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x0 = (index % width + QUIET_ZONE) * PNG_MODULE_SIZE;
+        let y0 = (index / width + QUIET_ZONE) * PNG_MODULE_SIZE;
+        for y in y0..y0 + PNG_MODULE_SIZE {
+            pixels[y * size + x0..y * size + x0 + PNG_MODULE_SIZE].fill(0);
+        }
+    }
+
+    let mut output = Vec::new();
+    let mut encoder = png::Encoder::new(&mut output, size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&pixels).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(output)
+}
+
+fn render(url: &str) -> Result<TunnelQr, String> {
+    let code = QrCode::new(url.as_bytes()).map_err(|e| format!("Failed to encode QR code: {}", e))?;
+    let svg = code
+        .render::<svg::Color>()
+        .min_dimensions(SVG_MIN_SIZE, SVG_MIN_SIZE)
+        .build();
+    let png = format!("data:image/png;base64,{}", BASE64.encode(render_png(&code)?));
+    Ok(TunnelQr { url: url.to_string(), svg, png })
+}
+
+fn cached_render(url: &str) -> Result<TunnelQr, String> {
+    let mut cache = CACHE.lock().unwrap();
+    if let Some(qr) = cache.iter().find(|qr| qr.url == url) {
+        return Ok(qr.clone());
+    }
+    let qr = render(url)?;
+    cache.push(qr.clone());
+    Ok(qr)
+}
+
+// Without the token the code only opens the tunnel for devices that already
+// have access, so it is included unless asked otherwise
+pub fn tunnel_qr(state: &AppState, include_token: bool) -> Result<TunnelQr, String> {
+    let url = if include_token {
+        share_url(state)
+    } else {
+        state.tunnel_url.lock().unwrap().clone()
+    };
+    cached_render(&url.ok_or_else(|| "No tunnel URL available".to_string())?)
+}
+
+// Rebuild on every new tunnel URL and push the shareable code as `tunnel-qr`
+pub fn spawn_regenerator(host: Arc<dyn Host>) {
+    let listener = host.clone_host();
+    host.subscribe("tunnel-url", Box::new(move |_| {
+        CACHE.lock().unwrap().clear();
+        match tunnel_qr(listener.app_state(), true) {
+            Ok(qr) => listener.publish("tunnel-qr", serde_json::to_value(qr).unwrap_or_default()),
+            Err(err) => log::warn!("Failed to render tunnel QR code: {}", err),
+        }
+    }));
+}
+
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub fn get_tunnel_qr(state: tauri::State<AppState>, include_token: Option<bool>) -> Result<TunnelQr, String> {
+    tunnel_qr(&state, include_token.unwrap_or(true))
+}
@@ -0,0 +1,135 @@
+use std::borrow::Cow;
+use std::sync::{OnceLock, RwLock};
+use regex::Regex;
+
+use crate::settings;
+
+const REDACTED: &str = "[REDACTED]";
+
+// Each pattern keeps its first capture group (the key/prefix) and masks the rest
+const BUILTIN_PATTERNS: [&str; 6] = [
+    // mt_auth session cookie
+    r"(?i)((?:mt_auth|mt_tunnel)=)[^;\s&]+",
+    // Authorization headers and bearer tokens
+    r"(?i)(authorization:\s*)(?:(?:bearer|basic|token)\s+)?\S+",
+    r"(?i)(bearer\s+)[A-Za-z0-9\-._~+/]+=*",
+    // cloudflared tunnel tokens passed as flags or env
+    r"(?i)(--token[=\s]+|TUNNEL_TOKEN=)\S+",
+    // Secrets in URL query strings
+    r"(?i)([?&](?:token|access_token|auth|key|api_key|secret|password|pin|sig|signature)=)[^&\s#]+",
+    // key=value / key: value pairs in process output and JSON
+    r#"(?i)((?:"|\b)(?:password|passwd|secret|pin|auth_token|access_token|api_key)"?\s*[:=]\s*"?)[^"\s,&}]+"#,
+];
+
+struct Redactor {
+    builtin: Vec<Regex>,
+    extra: Vec<Regex>,
+}
+
+fn redactor() -> &'static RwLock<Redactor> {
+    static REDACTOR: OnceLock<RwLock<Redactor>> = OnceLock::new();
+    REDACTOR.get_or_init(|| {
+        let builtin = BUILTIN_PATTERNS
+            .iter()
+            .map(|pattern| Regex::new(pattern).expect("built-in redaction pattern is valid"))
+            .collect();
+
+        // Extra patterns come from settings once they are loaded; see
+        // settings::apply_runtime
+        RwLock::new(Redactor { builtin, extra: Vec::new() })
+    })
+}
+
+fn compile_patterns<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<Vec<Regex>, String> {
+    patterns
+        .into_iter()
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| Regex::new(pattern).map_err(|e| format!("Invalid pattern {:?}: {}", pattern, e)))
+        .collect()
+}
+
+// Mask secrets in a line before it is logged or emitted to the webview
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let Ok(redactor) = redactor().read() else {
+        return Cow::Borrowed(text);
+    };
+
+    let mut result = Cow::Borrowed(text);
+    for regex in &redactor.builtin {
+        if regex.is_match(&result) {
+            let replaced = regex.replace_all(&result, format!("${{1}}{}", REDACTED)).into_owned();
+            result = Cow::Owned(replaced);
+        }
+    }
+    // Extra patterns mask their whole match
+    for regex in &redactor.extra {
+        if regex.is_match(&result) {
+            let replaced = regex.replace_all(&result, REDACTED).into_owned();
+            result = Cow::Owned(replaced);
+        }
+    }
+    result
+}
+
+pub fn validate_patterns(patterns: &[String]) -> Result<(), String> {
+    compile_patterns(patterns.iter().map(String::as_str)).map(|_| ())
+}
+
+pub fn set_extra_patterns(patterns: &[String]) -> Result<(), String> {
+    let compiled = compile_patterns(patterns.iter().map(String::as_str))?;
+    redactor().write().map_err(|e| e.to_string())?.extra = compiled;
+    Ok(())
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn get_redaction_patterns() -> Vec<String> {
+    redactor()
+        .read()
+        .map(|redactor| redactor.extra.iter().map(|regex| regex.as_str().to_string()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn set_redaction_patterns(patterns: Vec<String>) -> Result<(), String> {
+    settings::modify(|settings| settings.logging.redaction_patterns = patterns.clone())?;
+    set_extra_patterns(&patterns)?;
+    log::info!("Updated extra redaction patterns ({} configured)", patterns.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_builtin_secrets_and_keeps_their_prefix() {
+        assert_eq!(redact("Cookie: mt_auth=abc123; path=/"), "Cookie: mt_auth=[REDACTED]; path=/");
+        assert_eq!(redact("Authorization: Bearer eyJhbGciOi"), "Authorization: [REDACTED]");
+        assert_eq!(redact("cloudflared --token eyJhIjoi"), "cloudflared --token [REDACTED]");
+        assert_eq!(
+            redact("GET /?token=s3cret&view=1 HTTP/1.1"),
+            "GET /?token=[REDACTED]&view=1 HTTP/1.1"
+        );
+        assert_eq!(redact(r#"{"password": "hunter2"}"#), r#"{"password": "[REDACTED]"}"#);
+    }
+
+    #[test]
+    fn leaves_clean_text_borrowed() {
+        assert!(matches!(redact("Server listening on port 3456"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn extra_patterns_mask_the_whole_match() {
+        set_extra_patterns(&["sk-[a-z0-9]+".to_string(), "  ".to_string()]).unwrap();
+        assert_eq!(redact("key sk-abc123 used"), "key [REDACTED] used");
+        assert_eq!(get_redaction_patterns(), vec!["sk-[a-z0-9]+".to_string()]);
+        set_extra_patterns(&[]).unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(validate_patterns(&["(unclosed".to_string()]).is_err());
+        assert!(validate_patterns(&["ok".to_string(), String::new()]).is_ok());
+    }
+}
@@ -0,0 +1,276 @@
+use std::process::Child;
+use std::sync::{Mutex, TryLockError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+#[cfg(feature = "gui")]
+use tauri::{AppHandle, Manager};
+
+#[cfg(feature = "gui")]
+use crate::{diagnostics, notifications, tray, updates};
+use crate::{
+    autostart, copy_share_url, get_app_version, get_log_level, is_claude_code_installed, is_claude_mem_installed,
+    lan, logs, monitor, qr, redact, secrets, server_url, set_log_level, settings, share_url, start_server_internal, start_tunnel_internal,
+    stop_server_internal, stop_tunnel_internal, wait_for_server_health, AppState,
+    Host,
+};
+
+// JSON-RPC 2.0 error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+// A command ran and returned an error
+pub const COMMAND_FAILED: i64 = -32000;
+// The method needs the desktop app and this is the daemon
+pub const UNAVAILABLE: i64 = -32001;
+
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+// Named parameter; missing params deserialize as null so Option<T> is optional
+pub fn param<T: DeserializeOwned>(params: &Value, name: &str) -> Result<T, RpcError> {
+    let value = params.get(name).cloned().unwrap_or(Value::Null);
+    serde_json::from_value(value)
+        .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid `{}`: {}", name, e)))
+}
+
+fn to_value<T: Serialize>(result: Result<T, String>) -> Result<Value, RpcError> {
+    result
+        .map_err(|e| RpcError::new(COMMAND_FAILED, e))
+        .and_then(|value| {
+            serde_json::to_value(value).map_err(|e| RpcError::new(COMMAND_FAILED, e.to_string()))
+        })
+}
+
+#[cfg(feature = "gui")]
+fn app(host: &dyn Host, method: &str) -> Result<AppHandle, RpcError> {
+    host.app_handle()
+        .ok_or_else(|| RpcError::new(UNAVAILABLE, format!("{} is only available in the desktop app", method)))
+}
+
+// A process mutex stays locked while it is being started
+fn process_status(process: &Mutex<Option<Child>>) -> &'static str {
+    match process.try_lock() {
+        Ok(mut guard) => match guard.as_mut().map(|child| child.try_wait()) {
+            Some(Ok(None)) => "running",
+            Some(_) => "exited",
+            None => "stopped",
+        },
+        Err(TryLockError::WouldBlock) => "starting",
+        Err(TryLockError::Poisoned(_)) => "error",
+    }
+}
+
+fn status(state: &AppState) -> Value {
+    let external_server = settings::get().server.external;
+    let tunnel_url = state.tunnel_url.lock().unwrap().clone();
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "pid": std::process::id(),
+        "server": if external_server { "external" } else { process_status(&state.server_process) },
+        "server_url": server_url(),
+        "server_healthy": wait_for_server_health(1, 0),
+        "sidecar": process_status(&state.sidecar_process),
+        "tunnel": process_status(&state.tunnel_process),
+        "tunnel_url": tunnel_url,
+    })
+}
+
+// Same behaviour as the Tauri command of the same name. Connection-level methods
+// (subscribe, unsubscribe, quit) are handled by the control socket.
+pub fn call(host: &dyn Host, method: &str, params: &Value) -> Result<Value, RpcError> {
+    let state = host.app_state();
+
+    match method {
+        "status" => Ok(status(state)),
+
+        // Server and tunnel
+        "get_tunnel_url" => to_value(Ok(state.tunnel_url.lock().unwrap().clone())),
+        "is_server_running" => to_value(Ok(state.server_process.lock().unwrap().is_some())),
+        "restart_server" => {
+            stop_server_internal(state);
+            to_value(start_server_internal(host, state).map_err(|e| e.to_string()))
+        }
+        "stop_server" => {
+            stop_server_internal(state);
+            host.publish("server-status", "stopped".into());
+            Ok(Value::Null)
+        }
+        "start_tunnel" => to_value(start_tunnel_internal(host, state).map_err(|e| e.to_string())),
+        "stop_tunnel" => {
+            stop_tunnel_internal(state);
+            host.publish("tunnel-status", "stopped".into());
+            Ok(Value::Null)
+        }
+        "restart_tunnel" => {
+            stop_tunnel_internal(state);
+            to_value(start_tunnel_internal(host, state).map_err(|e| e.to_string()))
+        }
+        "get_share_url" => to_value(Ok(share_url(state))),
+        "get_lan_urls" => to_value(Ok(lan::lan_urls(state))),
+        "get_tunnel_qr" => {
+            let include_token: Option<bool> = param(params, "include_token")?;
+            to_value(qr::tunnel_qr(state, include_token.unwrap_or(true)))
+        }
+        "copy_tunnel_url" => to_value(copy_share_url(host, state, param(params, "clear_after")?)),
+
+        // App info and logging
+        "get_app_version" => to_value(Ok(get_app_version())),
+        "is_claude_code_installed" => to_value(Ok(is_claude_code_installed())),
+        "is_claude_mem_installed" => to_value(Ok(is_claude_mem_installed())),
+        "get_log_level" => to_value(Ok(get_log_level())),
+        "set_log_level" => to_value(set_log_level(param(params, "level")?)),
+        "list_log_files" => to_value(Ok(logs::list_log_files())),
+        "read_log" => to_value(logs::read_log(
+            param(params, "name")?,
+            param(params, "offset")?,
+            param(params, "length")?,
+        )),
+        "tail_log" => to_value(logs::tail_log(param(params, "name")?, param(params, "lines")?)),
+        "search_log" => to_value(logs::search_log(
+            param(params, "name")?,
+            param(params, "pattern")?,
+            param(params, "max_results")?,
+        )),
+        "follow_log" => to_value(logs::follow(host.clone_host(), param(params, "name")?)),
+        "unfollow_log" => to_value(Ok(logs::unfollow_log(param(params, "subscription")?))),
+        "get_redaction_patterns" => to_value(Ok(redact::get_redaction_patterns())),
+        "set_redaction_patterns" => to_value(redact::set_redaction_patterns(param(params, "patterns")?)),
+        "set_secret" => to_value(secrets::set_secret(param(params, "name")?, param(params, "value")?)),
+        "delete_secret" => to_value(secrets::delete_secret(param(params, "name")?)),
+        "has_secret" => to_value(secrets::has_secret(param(params, "name")?)),
+
+        // Settings
+        "get_settings" => to_value(Ok(settings::get_settings())),
+        "update_settings" => to_value(settings::update(host, &param::<Value>(params, "patch")?)),
+
+        // Resource monitoring
+        "get_process_stats" => to_value(Ok(monitor::collect_stats(state))),
+        "get_resource_thresholds" => to_value(Ok(monitor::get_resource_thresholds())),
+        "set_resource_thresholds" => to_value(monitor::set_resource_thresholds(param(params, "thresholds")?)),
+
+        // Login item
+        "get_autostart" => to_value(Ok(autostart::get_autostart())),
+        "set_autostart" => to_value(autostart::set_autostart(param(params, "enabled")?, param(params, "start_hidden")?)),
+
+        _ => call_app(host, method, params),
+    }
+}
+
+// Methods backed by the desktop app's updater, tray, notifications or dialogs
+#[cfg(feature = "gui")]
+fn call_app(host: &dyn Host, method: &str, params: &Value) -> Result<Value, RpcError> {
+    match method {
+        "export_diagnostics" => {
+            let app = app(host, method)?;
+            let destination = param(params, "destination")?;
+            to_value(tauri::async_runtime::block_on(diagnostics::export_diagnostics(app, destination)))
+        }
+
+        // Updates
+        "check_for_updates" => {
+            to_value(tauri::async_runtime::block_on(updates::check_for_updates(app(host, method)?)))
+        }
+        "install_update" => {
+            to_value(tauri::async_runtime::block_on(updates::install_update(app(host, method)?)))
+        }
+        "download_update" => {
+            to_value(tauri::async_runtime::block_on(updates::download_update(app(host, method)?)))
+        }
+        "install_update_when_idle" => {
+            to_value(tauri::async_runtime::block_on(updates::install_update_when_idle(app(host, method)?)))
+        }
+        "get_update_channel" => to_value(Ok(updates::get_update_channel(app(host, method)?.state()))),
+        "set_update_channel" => {
+            let app = app(host, method)?;
+            to_value(updates::set_update_channel(app.state(), param(params, "channel")?))
+        }
+        "set_update_endpoint" => {
+            let app = app(host, method)?;
+            to_value(updates::set_update_endpoint(
+                app.state(),
+                param(params, "channel")?,
+                param(params, "endpoint")?,
+            ))
+        }
+        "set_update_check_interval" => {
+            let app = app(host, method)?;
+            to_value(updates::set_update_check_interval(app.state(), param(params, "hours")?))
+        }
+        "skip_update_version" => {
+            let app = app(host, method)?;
+            to_value(updates::skip_update_version(app.state(), param(params, "version")?))
+        }
+        "clear_skipped_updates" => to_value(updates::clear_skipped_updates(app(host, method)?.state())),
+        "get_staged_update" => to_value(Ok(updates::get_staged_update(app(host, method)?.state()))),
+        "install_staged_update" => {
+            to_value(updates::install_staged_update(app(host, method)?, param(params, "restart")?))
+        }
+        "cancel_update_download" => to_value(Ok(updates::cancel_update_download(app(host, method)?.state()))),
+        "cancel_idle_install" => to_value(Ok(updates::cancel_idle_install(app(host, method)?.state()))),
+        "set_update_idle_seconds" => {
+            let app = app(host, method)?;
+            to_value(updates::set_update_idle_seconds(app.state(), param(params, "seconds")?))
+        }
+
+        // Tray
+        "get_tray_enabled" => to_value(Ok(tray::get_tray_enabled(app(host, method)?.state()))),
+        "set_tray_enabled" => {
+            to_value(tray::set_tray_enabled(app(host, method)?, param(params, "enabled")?))
+        }
+
+        // Notifications
+        "get_notification_preferences" => {
+            to_value(Ok(notifications::get_notification_preferences(app(host, method)?.state())))
+        }
+        "set_notification_preferences" => {
+            let app = app(host, method)?;
+            to_value(notifications::set_notification_preferences(app.state(), param(params, "preferences")?))
+        }
+
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    }
+}
+
+// Built without the `gui` feature: the methods above exist only in the desktop app
+#[cfg(not(feature = "gui"))]
+const APP_METHODS: &[&str] = &[
+    "export_diagnostics",
+    "check_for_updates",
+    "install_update",
+    "download_update",
+    "install_update_when_idle",
+    "get_update_channel",
+    "set_update_channel",
+    "set_update_endpoint",
+    "set_update_check_interval",
+    "skip_update_version",
+    "clear_skipped_updates",
+    "get_staged_update",
+    "install_staged_update",
+    "cancel_update_download",
+    "cancel_idle_install",
+    "set_update_idle_seconds",
+    "get_tray_enabled",
+    "set_tray_enabled",
+    "get_notification_preferences",
+    "set_notification_preferences",
+];
+
+#[cfg(not(feature = "gui"))]
+fn call_app(_host: &dyn Host, method: &str, _params: &Value) -> Result<Value, RpcError> {
+    if APP_METHODS.contains(&method) {
+        Err(RpcError::new(UNAVAILABLE, format!("{} is only available in the desktop app", method)))
+    } else {
+        Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method)))
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+
+use crate::default_config_dir;
+
+const SERVICE: &str = "com.terminaltunnel.app";
+const NONCE_LEN: usize = 12;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Backend {
+    // macOS Keychain, Secret Service on Linux, Credential Manager on Windows
+    Keyring,
+    // ChaCha20-Poly1305 file for machines without a keyring (headless Linux)
+    EncryptedFile,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SecretFile {
+    version: u32,
+    // name -> base64(nonce || ciphertext), with the name as associated data
+    secrets: BTreeMap<String, String>,
+}
+
+// Serializes read-modify-write of the encrypted file
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+fn secrets_path() -> PathBuf {
+    default_config_dir().join("secrets.enc")
+}
+
+fn key_path() -> PathBuf {
+    default_config_dir().join("secrets.key")
+}
+
+// MT_SECRETS_BACKEND=keyring|file forces a backend; otherwise use the keyring
+// when a probe lookup works
+fn backend() -> Backend {
+    static BACKEND: OnceLock<Backend> = OnceLock::new();
+    *BACKEND.get_or_init(|| {
+        let backend = match std::env::var("MT_SECRETS_BACKEND").ok().as_deref() {
+            Some("file") => Backend::EncryptedFile,
+            Some("keyring") => Backend::Keyring,
+            _ => match keyring::Entry::new(SERVICE, "keyring-probe").and_then(|entry| entry.get_password()) {
+                Ok(_) | Err(keyring::Error::NoEntry) => Backend::Keyring,
+                Err(err) => {
+                    log::warn!("System keyring unavailable ({}); using encrypted file", err);
+                    Backend::EncryptedFile
+                }
+            },
+        };
+        log::info!("Secret storage backend: {:?}", backend);
+        backend
+    })
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid secret name: {:?}", name))
+    }
+}
+
+// Owner-only write via a temp file so a crash never leaves a half-written file
+pub fn write_private(path: &Path, content: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp = path.with_extension("tmp");
+    // Created 0600 rather than chmod'ed afterwards, so it is never readable by
+    // others; a temp file left by a crash is replaced
+    let _ = fs::remove_file(&temp);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temp).map_err(|e| format!("Failed to create {:?}: {}", temp, e))?;
+    file.write_all(content)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write {:?}: {}", temp, e))?;
+    drop(file);
+    fs::rename(&temp, path).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+// MT_SECRETS_KEY (base64) supplies the key on headless machines; otherwise a
+// random key is kept in its own owner-only file next to the secrets. That only
+// obscures them: anyone who can read secrets.enc can read the key too.
+fn file_cipher() -> Result<ChaCha20Poly1305, String> {
+    let key = match std::env::var("MT_SECRETS_KEY") {
+        Ok(encoded) => BASE64.decode(encoded.trim()).map_err(|e| format!("Invalid MT_SECRETS_KEY: {}", e))?,
+        Err(_) => match fs::read(key_path()) {
+            Ok(key) => key,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let key = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+                write_private(&key_path(), &key)?;
+                key
+            }
+            Err(err) => return Err(format!("Failed to read secrets key: {}", err)),
+        },
+    };
+    if key.len() != 32 {
+        return Err("Secrets key must be 32 bytes".to_string());
+    }
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+fn load_file() -> Result<SecretFile, String> {
+    match fs::read_to_string(secrets_path()) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Corrupt secrets file: {}", e)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(SecretFile { version: 1, ..Default::default() }),
+        Err(err) => Err(format!("Failed to read secrets file: {}", err)),
+    }
+}
+
+fn save_file(file: &SecretFile) -> Result<(), String> {
+    let content = serde_json::to_vec_pretty(file).map_err(|e| e.to_string())?;
+    write_private(&secrets_path(), &content)
+}
+
+fn encrypt(cipher: &ChaCha20Poly1305, name: &str, value: &str) -> Result<String, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: value.as_bytes(), aad: name.as_bytes() })
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(BASE64.encode(sealed))
+}
+
+fn decrypt(cipher: &ChaCha20Poly1305, name: &str, sealed: &str) -> Result<String, String> {
+    let sealed = BASE64.decode(sealed).map_err(|_| format!("Corrupt secret {:?}", name))?;
+    if sealed.len() < NONCE_LEN {
+        return Err(format!("Corrupt secret {:?}", name));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: name.as_bytes() })
+        .map_err(|_| format!("Failed to decrypt secret {:?}; was the key changed?", name))?;
+    String::from_utf8(plaintext).map_err(|_| format!("Secret {:?} is not UTF-8", name))
+}
+
+fn keyring_entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| e.to_string())
+}
+
+// For Rust callers only; secret values never go back to the webview
+pub fn get_secret(name: &str) -> Result<Option<String>, String> {
+    validate_name(name)?;
+    match backend() {
+        Backend::Keyring => match keyring_entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(err.to_string()),
+        },
+        Backend::EncryptedFile => {
+            let _guard = FILE_LOCK.lock().unwrap();
+            let file = load_file()?;
+            match file.secrets.get(name) {
+                Some(sealed) => decrypt(&file_cipher()?, name, sealed).map(Some),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+pub fn store_secret(name: &str, value: &str) -> Result<(), String> {
+    validate_name(name)?;
+    match backend() {
+        Backend::Keyring => keyring_entry(name)?.set_password(value).map_err(|e| e.to_string()),
+        Backend::EncryptedFile => {
+            let _guard = FILE_LOCK.lock().unwrap();
+            let mut file = load_file()?;
+            let sealed = encrypt(&file_cipher()?, name, value)?;
+            file.secrets.insert(name.to_string(), sealed);
+            save_file(&file)
+        }
+    }
+}
+
+pub fn remove_secret(name: &str) -> Result<bool, String> {
+    validate_name(name)?;
+    match backend() {
+        Backend::Keyring => match keyring_entry(name)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(err) => Err(err.to_string()),
+        },
+        Backend::EncryptedFile => {
+            let _guard = FILE_LOCK.lock().unwrap();
+            let mut file = load_file()?;
+            let removed = file.secrets.remove(name).is_some();
+            if removed {
+                save_file(&file)?;
+            }
+            Ok(removed)
+        }
+    }
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn set_secret(name: String, value: String) -> Result<(), String> {
+    store_secret(&name, &value)?;
+    log::info!("Stored secret {:?}", name);
+    Ok(())
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn delete_secret(name: String) -> Result<bool, String> {
+    let removed = remove_secret(&name)?;
+    if removed {
+        log::info!("Deleted secret {:?}", name);
+    }
+    Ok(removed)
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn has_secret(name: String) -> Result<bool, String> {
+    get_secret(&name).map(|value| value.is_some())
+}
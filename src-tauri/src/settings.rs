@@ -0,0 +1,674 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "gui")]
+use tauri::{AppHandle, Manager};
+use url::Url;
+
+use crate::monitor::{self, ResourceThresholds};
+#[cfg(feature = "gui")]
+use crate::{notifications, tray, updates};
+use crate::{default_config_dir, lan, redact, secrets, Host};
+
+// Bump when the layout changes and add a step to MIGRATIONS
+pub const SETTINGS_VERSION: u32 = 1;
+
+// MIGRATIONS[n] turns a version n file into version n + 1
+const MIGRATIONS: [fn(&mut Value); 1] = [migrate_v0];
+
+const PTY_BACKENDS: [&str; 2] = ["sidecar", "direct"];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerSettings {
+    // Use a server started outside the app (MT_EXTERNAL_SERVER=1) at
+    // external_host:external_port, which may be another machine or a container
+    pub external: bool,
+    pub external_host: String,
+    pub external_port: u16,
+    pub pty_backend: String,
+    pub pty_sidecar_url: String,
+    // Default to server.log / pty-sidecar.log in the log directory
+    pub server_log: Option<PathBuf>,
+    pub pty_sidecar_log: Option<PathBuf>,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            external: false,
+            external_host: "127.0.0.1".to_string(),
+            external_port: 3456,
+            pty_backend: "sidecar".to_string(),
+            pty_sidecar_url: "http://127.0.0.1:3457".to_string(),
+            server_log: None,
+            pty_sidecar_log: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingSettings {
+    pub level: String,
+    // Extra regexes masked in logs, on top of the built-in ones
+    pub redaction_patterns: Vec<String>,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        Self { level: "info".to_string(), redaction_patterns: Vec::new() }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    // Clear a copied tunnel URL after this many seconds
+    pub clear_after_seconds: Option<u64>,
+}
+
+// When the public tunnel comes up: at launch, only when started from the UI,
+// tray or CLI, or not at all
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TunnelStartup {
+    #[default]
+    Always,
+    OnDemand,
+    Never,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TunnelSettings {
+    pub startup: TunnelStartup,
+    // Stop the tunnel after this many minutes without a remote request
+    pub idle_stop_minutes: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanSettings {
+    // Serve other machines on the local network directly, gated by the same
+    // access token as the tunnel
+    pub enabled: bool,
+    // Announce the server over mDNS as _terminaltunnel._tcp
+    pub advertise: bool,
+}
+
+impl Default for LanSettings {
+    fn default() -> Self {
+        Self { enabled: false, advertise: true }
+    }
+}
+
+// Sections owned by the desktop app's tray, notifications and updater. They
+// live here so the daemon reads and writes the same file without them.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrayPreferences {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for TrayPreferences {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+// Which events raise a desktop notification; all on by default
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationPreferences {
+    pub tunnel_url: bool,
+    pub tunnel_lost: bool,
+    pub server_crashed: bool,
+    pub update_available: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            tunnel_url: true,
+            tunnel_lost: true,
+            server_crashed: true,
+            update_available: true,
+        }
+    }
+}
+
+const DEFAULT_CHECK_INTERVAL_HOURS: u64 = 6;
+const DEFAULT_IDLE_INSTALL_SECONDS: u64 = 120;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UpdatePreferences {
+    #[serde(default)]
+    pub channel: UpdateChannel,
+    // Per-channel endpoint overrides (https, http in dev builds, or file://)
+    #[serde(default)]
+    pub stable_endpoint: Option<String>,
+    #[serde(default)]
+    pub beta_endpoint: Option<String>,
+    // Background checks; an interval of 0 disables them
+    #[serde(default = "default_check_interval_hours")]
+    pub check_interval_hours: u64,
+    #[serde(default)]
+    pub skipped_versions: Vec<String>,
+    // How long every terminal must be quiet before an "install when idle" proceeds
+    #[serde(default = "default_idle_install_seconds")]
+    pub idle_install_seconds: u64,
+}
+
+fn default_check_interval_hours() -> u64 {
+    DEFAULT_CHECK_INTERVAL_HOURS
+}
+
+fn default_idle_install_seconds() -> u64 {
+    DEFAULT_IDLE_INSTALL_SECONDS
+}
+
+impl Default for UpdatePreferences {
+    fn default() -> Self {
+        Self {
+            channel: UpdateChannel::default(),
+            stable_endpoint: None,
+            beta_endpoint: None,
+            check_interval_hours: DEFAULT_CHECK_INTERVAL_HOURS,
+            skipped_versions: Vec::new(),
+            idle_install_seconds: DEFAULT_IDLE_INSTALL_SECONDS,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub server: ServerSettings,
+    pub tunnel: TunnelSettings,
+    pub lan: LanSettings,
+    pub logging: LoggingSettings,
+    pub tray: TrayPreferences,
+    pub notifications: NotificationPreferences,
+    pub clipboard: ClipboardSettings,
+    pub resources: ResourceThresholds,
+    pub updates: UpdatePreferences,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            server: ServerSettings::default(),
+            tunnel: TunnelSettings::default(),
+            lan: LanSettings::default(),
+            logging: LoggingSettings::default(),
+            tray: TrayPreferences::default(),
+            notifications: NotificationPreferences::default(),
+            clipboard: ClipboardSettings::default(),
+            resources: ResourceThresholds::default(),
+            updates: UpdatePreferences::default(),
+        }
+    }
+}
+
+// Effective settings plus the env vars currently overriding them
+#[derive(Clone, Serialize)]
+pub struct SettingsSnapshot {
+    pub settings: Settings,
+    pub env_overrides: Vec<&'static str>,
+}
+
+fn settings_path() -> PathBuf {
+    default_config_dir().join("settings.json")
+}
+
+// Saved settings; env overrides are applied on read and never written back
+fn store() -> &'static RwLock<Settings> {
+    static STORE: OnceLock<RwLock<Settings>> = OnceLock::new();
+    STORE.get_or_init(|| RwLock::new(load()))
+}
+
+// load() first runs before the app's log plugin is registered, so what it has
+// to say waits here until report_load() logs it
+static LOAD_MESSAGES: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+fn note(level: log::Level, message: String) {
+    LOAD_MESSAGES.lock().unwrap().push((level, message));
+}
+
+// Call once logging is set up
+pub fn report_load() {
+    store();
+    for (level, message) in LOAD_MESSAGES.lock().unwrap().drain(..) {
+        log::log!(level, "{}", message);
+    }
+}
+
+// Top-level sections; each is validated and, if broken, reset on its own
+const SECTIONS: [&str; 9] = [
+    "server", "tunnel", "lan", "logging", "tray", "notifications", "clipboard", "resources", "updates",
+];
+
+// Before versioning each subsystem kept its own file
+const LEGACY_FILES: [(&str, &str); 3] = [("tray", "tray.json"), ("notifications", "notifications.json"), ("updates", "updates.json")];
+
+fn migrate_v0(value: &mut Value) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    for (section, file) in LEGACY_FILES {
+        let path = default_config_dir().join(file);
+        let Some(legacy) = fs::read_to_string(&path).ok().and_then(|content| serde_json::from_str::<Value>(&content).ok()) else {
+            continue;
+        };
+        object.entry(section).or_insert(legacy);
+    }
+}
+
+// Only once settings.json holds their contents, so a failed save retries the
+// migration on the next launch
+fn retire_legacy_files() {
+    for (_, file) in LEGACY_FILES {
+        let path = default_config_dir().join(file);
+        if path.exists() {
+            let _ = fs::rename(&path, path.with_extension("json.migrated"));
+        }
+    }
+}
+
+// Drops sections that fail to parse or validate so they fall back to their
+// defaults while the rest of the file is kept. Returns whether any were dropped.
+fn repair(value: &mut Value) -> bool {
+    let Some(object) = value.as_object_mut() else {
+        return false;
+    };
+    let mut repaired = false;
+    for section in SECTIONS {
+        let Some(part) = object.get(section) else {
+            continue;
+        };
+        let problem = match serde_json::from_value::<Settings>(serde_json::json!({ section: part })) {
+            Ok(parsed) => validate_section(&parsed, section).err(),
+            Err(err) => Some(err.to_string()),
+        };
+        if let Some(problem) = problem {
+            note(log::Level::Warn, format!("Resetting invalid {} settings to defaults: {}", section, problem));
+            object.remove(section);
+            repaired = true;
+        }
+    }
+    repaired
+}
+
+fn load() -> Settings {
+    let path = settings_path();
+    let mut value = match fs::read_to_string(&path) {
+        Ok(content) => match serde_json::from_str::<Value>(&content) {
+            Ok(value) if value.is_object() => value,
+            _ => {
+                note(log::Level::Warn, format!("Settings file {:?} is not valid JSON; using defaults", path));
+                let _ = fs::rename(&path, path.with_extension("json.invalid"));
+                Value::Object(Default::default())
+            }
+        },
+        Err(_) => Value::Object(Default::default()),
+    };
+
+    let version = value["version"].as_u64().unwrap_or(0) as u32;
+    if version > SETTINGS_VERSION {
+        note(log::Level::Warn, format!("Settings file is from a newer version ({}); unknown fields are ignored", version));
+    }
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        note(log::Level::Info, format!("Migrating settings from version {} to {}", from, from + 1));
+        migrate(&mut value);
+        value["version"] = Value::from(from + 1);
+    }
+
+    let repaired = repair(&mut value);
+    let settings = match serde_json::from_value::<Settings>(value) {
+        Ok(settings) => settings,
+        Err(err) => {
+            note(log::Level::Warn, format!("Failed to read settings ({}); using defaults", err));
+            return Settings::default();
+        }
+    };
+    if repaired {
+        // Keep what the user had so the reset values can be recovered by hand
+        let _ = fs::copy(&path, path.with_extension("json.invalid"));
+    }
+    if version < SETTINGS_VERSION || repaired {
+        match save(&settings) {
+            Ok(()) if version == 0 => retire_legacy_files(),
+            Ok(()) => {}
+            Err(err) => note(log::Level::Warn, format!("Failed to save migrated or repaired settings: {}", err)),
+        }
+    }
+    settings
+}
+
+// Through a temp file; a crash mid-write would otherwise leave truncated JSON
+// that the next load throws away
+fn save(settings: &Settings) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    secrets::write_private(&settings_path(), content.as_bytes())
+}
+
+fn validate(settings: &Settings) -> Result<(), String> {
+    SECTIONS.iter().try_for_each(|section| validate_section(settings, section))
+}
+
+fn validate_section(settings: &Settings, section: &str) -> Result<(), String> {
+    match section {
+        "server" => validate_server(&settings.server),
+        "tunnel" if settings.tunnel.idle_stop_minutes == Some(0) => Err("idle_stop_minutes must be positive".to_string()),
+        "logging" => {
+            settings.logging.level
+                .parse::<log::LevelFilter>()
+                .map_err(|_| format!("Invalid log level: {}", settings.logging.level))?;
+            redact::validate_patterns(&settings.logging.redaction_patterns)
+        }
+        "clipboard" if settings.clipboard.clear_after_seconds == Some(0) => {
+            Err("clear_after_seconds must be positive".to_string())
+        }
+        "resources" => monitor::validate_thresholds(&settings.resources),
+        "updates" => {
+            for endpoint in [&settings.updates.stable_endpoint, &settings.updates.beta_endpoint].into_iter().flatten() {
+                endpoint
+                    .parse::<Url>()
+                    .map_err(|e| format!("Invalid update endpoint {:?}: {}", endpoint, e))?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_server(server: &ServerSettings) -> Result<(), String> {
+    let external_host = &server.external_host;
+    if external_host.is_empty() || external_host.contains(&['/', '@', ' '][..]) {
+        return Err(format!("Invalid external_host: {:?}", server.external_host));
+    }
+    if server.external_port == 0 {
+        return Err("external_port must be positive".to_string());
+    }
+
+    if !PTY_BACKENDS.contains(&server.pty_backend.as_str()) {
+        return Err(format!("pty_backend must be one of {:?}", PTY_BACKENDS));
+    }
+    let sidecar_url = server.pty_sidecar_url
+        .parse::<Url>()
+        .map_err(|e| format!("Invalid pty_sidecar_url: {}", e))?;
+    if !matches!(sidecar_url.scheme(), "http" | "https") {
+        return Err("pty_sidecar_url must be an http(s) URL".to_string());
+    }
+    Ok(())
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+// Env vars win over the file so existing launch scripts keep working
+fn apply_env_overrides(settings: &mut Settings) -> Vec<&'static str> {
+    let mut overrides = Vec::new();
+    if let Some(value) = env("MT_EXTERNAL_SERVER") {
+        settings.server.external = value == "1";
+        overrides.push("MT_EXTERNAL_SERVER");
+    }
+    if let Some(value) = env("MT_EXTERNAL_HOST") {
+        settings.server.external_host = value;
+        overrides.push("MT_EXTERNAL_HOST");
+    }
+    if let Some(port) = env("MT_EXTERNAL_PORT").and_then(|value| value.trim().parse().ok()) {
+        settings.server.external_port = port;
+        overrides.push("MT_EXTERNAL_PORT");
+    }
+    if let Some(value) = env("PTY_BACKEND") {
+        settings.server.pty_backend = value;
+        overrides.push("PTY_BACKEND");
+    }
+    if let Some(value) = env("PTY_SIDECAR_URL") {
+        settings.server.pty_sidecar_url = value;
+        overrides.push("PTY_SIDECAR_URL");
+    }
+    if let Some(value) = env("SERVER_LOG") {
+        settings.server.server_log = Some(PathBuf::from(value));
+        overrides.push("SERVER_LOG");
+    }
+    if let Some(value) = env("PTY_SIDECAR_LOG") {
+        settings.server.pty_sidecar_log = Some(PathBuf::from(value));
+        overrides.push("PTY_SIDECAR_LOG");
+    }
+    if let Some(startup) = env("MT_TUNNEL_STARTUP").and_then(|value| serde_json::from_value(Value::String(value)).ok()) {
+        settings.tunnel.startup = startup;
+        overrides.push("MT_TUNNEL_STARTUP");
+    }
+    // 0 turns the idle stop off for this launch
+    if let Some(minutes) = env("MT_TUNNEL_IDLE_MINUTES").and_then(|value| value.trim().parse().ok()) {
+        settings.tunnel.idle_stop_minutes = Some(minutes).filter(|minutes| *minutes > 0);
+        overrides.push("MT_TUNNEL_IDLE_MINUTES");
+    }
+    if let Some(value) = env("MT_LAN") {
+        settings.lan.enabled = value == "1";
+        overrides.push("MT_LAN");
+    }
+    if let Some(value) = env("MT_LOG_LEVEL").filter(|value| value.parse::<log::LevelFilter>().is_ok()) {
+        settings.logging.level = value;
+        overrides.push("MT_LOG_LEVEL");
+    }
+    // Newline-separated regexes
+    if let Some(value) = env("MT_REDACT_PATTERNS") {
+        settings.logging.redaction_patterns = value.lines().map(str::to_string).collect();
+        overrides.push("MT_REDACT_PATTERNS");
+    }
+    // MT_TRAY=0 hides the tray for this launch without touching the saved setting
+    if env("MT_TRAY").as_deref() == Some("0") {
+        settings.tray.enabled = false;
+        overrides.push("MT_TRAY");
+    }
+    if let Some(seconds) = env("MT_CLIPBOARD_CLEAR_SECONDS").and_then(|value| value.trim().parse().ok()) {
+        settings.clipboard.clear_after_seconds = Some(seconds).filter(|seconds| *seconds > 0);
+        overrides.push("MT_CLIPBOARD_CLEAR_SECONDS");
+    }
+    overrides
+}
+
+pub fn snapshot() -> SettingsSnapshot {
+    let mut settings = store().read().unwrap().clone();
+    let env_overrides = apply_env_overrides(&mut settings);
+    SettingsSnapshot { settings, env_overrides }
+}
+
+// Effective settings: the saved file with env overrides applied
+pub fn get() -> Settings {
+    snapshot().settings
+}
+
+// Where settings-changed goes for changes made through modify(); set once the
+// app or daemon is up
+static PUBLISHER: OnceLock<Arc<dyn Host>> = OnceLock::new();
+
+pub fn attach(host: Arc<dyn Host>) {
+    let _ = PUBLISHER.set(host);
+}
+
+fn announce(host: &dyn Host, snapshot: &SettingsSnapshot) {
+    host.publish("settings-changed", serde_json::to_value(snapshot).unwrap_or_default());
+}
+
+fn commit(stored: &mut Settings, mut updated: Settings) -> Result<(), String> {
+    updated.version = SETTINGS_VERSION;
+    validate(&updated)?;
+    save(&updated)?;
+    *stored = updated;
+    Ok(())
+}
+
+// Change and persist saved settings. Subsystem setters use this directly and
+// update their own state; it does not push values to other subsystems, but
+// subscribers still get `settings-changed`.
+pub fn modify(change: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+    {
+        let mut stored = store().write().unwrap();
+        let mut updated = stored.clone();
+        change(&mut updated);
+        commit(&mut stored, updated)?;
+    }
+    let snapshot = snapshot();
+    if let Some(host) = PUBLISHER.get() {
+        announce(host.as_ref(), &snapshot);
+    }
+    Ok(snapshot.settings)
+}
+
+// Recursive object merge; anything else replaces the old value
+fn merge(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+// Log level and redaction apply to the daemon too; the rest is desktop state
+pub fn apply_runtime(settings: &Settings) {
+    if let Ok(level) = settings.logging.level.parse() {
+        log::set_max_level(level);
+    }
+    if let Err(err) = redact::set_extra_patterns(&settings.logging.redaction_patterns) {
+        log::warn!("Failed to apply redaction patterns: {}", err);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn apply_app(app: &AppHandle, settings: &Settings) {
+    tray::apply_preferences(app, settings.tray.clone());
+    notifications::apply_preferences(&app.state(), settings.notifications.clone());
+    updates::apply_preferences(&app.state(), settings.updates.clone());
+}
+
+// Merge a partial settings object, validate, save, apply and announce it
+pub fn update(host: &dyn Host, patch: &Value) -> Result<SettingsSnapshot, String> {
+    if !patch.is_object() {
+        return Err("Settings patch must be an object".to_string());
+    }
+    {
+        let mut stored = store().write().unwrap();
+        let mut value = serde_json::to_value(&*stored).map_err(|e| e.to_string())?;
+        merge(&mut value, patch);
+        let updated = serde_json::from_value::<Settings>(value).map_err(|e| format!("Invalid settings: {}", e))?;
+        commit(&mut stored, updated)?;
+    }
+
+    let snapshot = snapshot();
+    apply_runtime(&snapshot.settings);
+    lan::apply(host, &snapshot.settings.lan);
+    #[cfg(feature = "gui")]
+    if let Some(app) = host.app_handle() {
+        apply_app(&app, &snapshot.settings);
+    }
+    log::info!("Settings updated");
+    announce(host, &snapshot);
+    Ok(snapshot)
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn get_settings() -> SettingsSnapshot {
+    snapshot()
+}
+
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub fn update_settings(app: AppHandle, patch: Value) -> Result<SettingsSnapshot, String> {
+    update(&app, &patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_replaces_leaves_and_keeps_siblings() {
+        let mut target = json!({ "server": { "external": false, "external_port": 3456 }, "lan": { "enabled": false } });
+        merge(&mut target, &json!({ "server": { "external_port": 4000 }, "lan": null }));
+        assert_eq!(
+            target,
+            json!({ "server": { "external": false, "external_port": 4000 }, "lan": null })
+        );
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(validate(&Settings::default()).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_bad_values() {
+        let mut settings = Settings::default();
+        settings.logging.level = "loud".to_string();
+        assert!(validate_section(&settings, "logging").is_err());
+        assert!(validate_section(&settings, "server").is_ok());
+
+        let mut settings = Settings::default();
+        settings.server.external_host = "user@host".to_string();
+        assert!(validate(&settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.server.pty_sidecar_url = "ftp://localhost".to_string();
+        assert!(validate(&settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.tunnel.idle_stop_minutes = Some(0);
+        assert!(validate_section(&settings, "tunnel").is_err());
+    }
+
+    #[test]
+    fn repair_resets_only_broken_sections() {
+        let mut value = json!({
+            "version": SETTINGS_VERSION,
+            "server": { "external_port": 4000 },
+            "logging": { "level": "loud" },
+            "lan": { "enabled": "yes" },
+        });
+        assert!(repair(&mut value));
+
+        let settings: Settings = serde_json::from_value(value).unwrap();
+        assert_eq!(settings.server.external_port, 4000);
+        assert_eq!(settings.logging.level, LoggingSettings::default().level);
+        assert!(!settings.lan.enabled);
+    }
+
+    #[test]
+    fn repair_keeps_valid_files_untouched() {
+        let mut value = json!({ "version": SETTINGS_VERSION, "lan": { "enabled": true } });
+        assert!(!repair(&mut value));
+        assert_eq!(value["lan"]["enabled"], json!(true));
+    }
+
+    #[test]
+    fn migrate_v0_leaves_non_objects_alone() {
+        let mut value = json!([1, 2]);
+        migrate_v0(&mut value);
+        assert_eq!(value, json!([1, 2]));
+    }
+}
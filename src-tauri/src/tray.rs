@@ -0,0 +1,269 @@
+use std::sync::Mutex;
+use std::thread;
+use tauri::{
+    menu::{Menu, MenuItem, PredefinedMenuItem},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Emitter, Listener, Manager,
+};
+
+use crate::app::show_main_window;
+use crate::settings::{self, TrayPreferences};
+use crate::{
+    copy_share_url, start_server_internal, start_tunnel_internal,
+    stop_server_internal, stop_tunnel_internal, wait_for_server_health, AppState,
+};
+
+const TRAY_ID: &str = "main";
+
+// Last status strings seen on the server-status / tunnel-status events
+pub struct TrayStatus {
+    server: Mutex<String>,
+    tunnel: Mutex<String>,
+    preferences: Mutex<TrayPreferences>,
+}
+
+impl Default for TrayStatus {
+    fn default() -> Self {
+        Self {
+            server: Mutex::new("stopped".to_string()),
+            tunnel: Mutex::new("stopped".to_string()),
+            preferences: Mutex::new(load_preferences()),
+        }
+    }
+}
+
+// MT_TRAY=0 is applied by settings as a launch-only override
+fn load_preferences() -> TrayPreferences {
+    settings::get().tray
+}
+
+fn save_preferences(preferences: &TrayPreferences) -> Result<(), String> {
+    settings::modify(|settings| settings.tray = preferences.clone()).map(|_| ())
+}
+
+// Built from the last status events rather than the process mutexes, which
+// start_server_internal / start_tunnel_internal hold while emitting
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let status = app.state::<TrayStatus>();
+    let server_status = status.server.lock().unwrap().clone();
+    let tunnel_status = status.tunnel.lock().unwrap().clone();
+    let server_running = matches!(server_status.as_str(), "starting" | "running" | "external");
+    let tunnel_running = matches!(tunnel_status.as_str(), "starting" | "connected");
+    let tunnel_url = app.state::<AppState>().tunnel_url.lock().unwrap().clone();
+
+    let server_label = MenuItem::with_id(app, "server_status", format!("Server: {}", server_status), false, None::<&str>)?;
+    let tunnel_label = MenuItem::with_id(app, "tunnel_status", format!("Tunnel: {}", tunnel_status), false, None::<&str>)?;
+    let url_label = MenuItem::with_id(
+        app,
+        "tunnel_url",
+        tunnel_url.as_deref().unwrap_or("No tunnel URL"),
+        false,
+        None::<&str>,
+    )?;
+    let show = MenuItem::with_id(app, "show", "Open Dashboard", true, None::<&str>)?;
+    let copy_url = MenuItem::with_id(app, "copy_url", "Copy Tunnel URL", tunnel_url.is_some(), None::<&str>)?;
+    let toggle_server = if server_running {
+        MenuItem::with_id(app, "stop_server", "Stop Server", true, None::<&str>)?
+    } else {
+        MenuItem::with_id(app, "start_server", "Start Server", true, None::<&str>)?
+    };
+    let toggle_tunnel = if tunnel_running {
+        MenuItem::with_id(app, "stop_tunnel", "Stop Tunnel", true, None::<&str>)?
+    } else {
+        MenuItem::with_id(app, "start_tunnel", "Start Tunnel", true, None::<&str>)?
+    };
+    let restart_server = MenuItem::with_id(app, "restart_server", "Restart Server", server_running, None::<&str>)?;
+    let restart_tunnel = MenuItem::with_id(app, "restart_tunnel", "Restart Tunnel", tunnel_running, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+
+    Menu::with_items(app, &[
+        &server_label,
+        &tunnel_label,
+        &url_label,
+        &PredefinedMenuItem::separator(app)?,
+        &show,
+        &copy_url,
+        &PredefinedMenuItem::separator(app)?,
+        &toggle_server,
+        &restart_server,
+        &toggle_tunnel,
+        &restart_tunnel,
+        &PredefinedMenuItem::separator(app)?,
+        &quit,
+    ])
+}
+
+// Rebuild the menu and tooltip from the current process state
+pub fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    match build_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(err) => log::warn!("Failed to rebuild tray menu: {}", err),
+    }
+
+    let status = app.state::<TrayStatus>();
+    let tooltip = format!(
+        "Terminal Tunnel\nServer: {}\nTunnel: {}",
+        status.server.lock().unwrap(),
+        status.tunnel.lock().unwrap()
+    );
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+// Run process actions off the main thread; tunnel start can block for a while
+fn run_action(app: &AppHandle, action: &str) {
+    let app = app.clone();
+    let action = action.to_string();
+    thread::spawn(move || {
+        let state = app.state::<AppState>();
+        let result = match action.as_str() {
+            "start_server" => start_server_internal(&app, &state),
+            "stop_server" => {
+                stop_server_internal(&state);
+                let _ = app.emit("server-status", "stopped");
+                Ok(())
+            }
+            "restart_server" => {
+                stop_server_internal(&state);
+                start_server_internal(&app, &state)
+            }
+            "start_tunnel" => {
+                if wait_for_server_health(1, 0) {
+                    start_tunnel_internal(&app, &state)
+                } else {
+                    Err("Server not ready".into())
+                }
+            }
+            "stop_tunnel" => {
+                stop_tunnel_internal(&state);
+                let _ = app.emit("tunnel-status", "stopped");
+                Ok(())
+            }
+            "restart_tunnel" => {
+                stop_tunnel_internal(&state);
+                start_tunnel_internal(&app, &state)
+            }
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            log::error!("Tray action {} failed: {}", action, err);
+        }
+        refresh(&app);
+    });
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        "show" => show_main_window(app),
+        "copy_url" => {
+            // Written natively so it works while the window is hidden
+            if let Err(err) = copy_share_url(app, &app.state::<AppState>(), None) {
+                log::warn!("Failed to copy tunnel URL: {}", err);
+            }
+        }
+        "quit" => {
+            // The exit handler stops child processes
+            app.exit(0);
+        }
+        action => run_action(app, action),
+    }
+}
+
+fn track_status(app: &AppHandle, event: &str) {
+    let handle = app.clone();
+    let event_name = event.to_string();
+    app.listen_any(event, move |event| {
+        if event_name != "tunnel-url" {
+            let status = serde_json::from_str::<String>(event.payload())
+                .unwrap_or_else(|_| event.payload().to_string());
+            let tray_status = handle.state::<TrayStatus>();
+            let slot = if event_name == "server-status" {
+                &tray_status.server
+            } else {
+                &tray_status.tunnel
+            };
+            *slot.lock().unwrap() = status;
+        }
+        // Menu updates hop to the main thread; never block the emitting thread on it
+        let handle = handle.clone();
+        thread::spawn(move || refresh(&handle));
+    });
+}
+
+fn create(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app)?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip("Terminal Tunnel")
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id.as_ref()))
+        // Linux trays never report clicks; the menu's "Open Dashboard" covers it there
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone()).icon_as_template(cfg!(target_os = "macos"));
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+// The icon only exists while enabled. Creating it can fail (no appindicator
+// on some Linux desktops); that is logged and the app carries on without it.
+fn show_tray(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    if !enabled {
+        app.remove_tray_by_id(TRAY_ID);
+        return Ok(());
+    }
+    if app.tray_by_id(TRAY_ID).is_some() {
+        return Ok(());
+    }
+    create(app).map_err(|e| {
+        log::warn!("Failed to create tray icon: {}", e);
+        format!("Failed to create tray icon: {}", e)
+    })
+}
+
+pub fn setup(app: &AppHandle) {
+    // Status is tracked even without an icon so one created later starts current
+    for event in ["server-status", "tunnel-status", "tunnel-url"] {
+        track_status(app, event);
+    }
+    let enabled = app.state::<TrayStatus>().preferences.lock().unwrap().enabled;
+    let _ = show_tray(app, enabled);
+}
+
+#[tauri::command]
+pub fn get_tray_enabled(status: tauri::State<TrayStatus>) -> bool {
+    status.preferences.lock().unwrap().enabled
+}
+
+// Used when settings change as a whole; see settings::update
+pub fn apply_preferences(app: &AppHandle, preferences: TrayPreferences) {
+    let enabled = preferences.enabled;
+    *app.state::<TrayStatus>().preferences.lock().unwrap() = preferences;
+    let _ = show_tray(app, enabled);
+}
+
+#[tauri::command]
+pub fn set_tray_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    {
+        let status = app.state::<TrayStatus>();
+        let mut preferences = status.preferences.lock().unwrap();
+        preferences.enabled = enabled;
+        save_preferences(&preferences)?;
+    }
+    show_tray(&app, enabled)
+}
@@ -0,0 +1,729 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+use tokio::sync::Notify;
+
+use crate::settings::{self, UpdateChannel, UpdatePreferences};
+use crate::{
+    server_get, start_server_internal, start_tunnel_internal, stop_server_internal,
+    stop_tunnel_internal, wait_for_server_health, AppState,
+};
+
+// Background checks never run more often than this, whatever the setting
+const MIN_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(100);
+const SPEED_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+// Beta builds are published to a rolling `beta` release; stable uses tauri.conf.json
+const DEFAULT_BETA_ENDPOINT: &str =
+    "https://github.com/kerpopule/TerminalTunnel/releases/download/beta/latest.json";
+
+fn endpoint_for(preferences: &UpdatePreferences, channel: UpdateChannel) -> Option<String> {
+    match channel {
+        UpdateChannel::Stable => preferences.stable_endpoint.clone(),
+        UpdateChannel::Beta => preferences
+            .beta_endpoint
+            .clone()
+            .or_else(|| Some(DEFAULT_BETA_ENDPOINT.to_string())),
+    }
+}
+
+// A downloaded, signature-verified update waiting to be installed
+struct StagedUpdate {
+    update: Update,
+    bytes: Vec<u8>,
+    channel: UpdateChannel,
+}
+
+#[derive(Clone, Serialize)]
+pub struct StagedUpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub body: Option<String>,
+    pub channel: UpdateChannel,
+    pub size: u64,
+}
+
+impl From<&StagedUpdate> for StagedUpdateInfo {
+    fn from(staged: &StagedUpdate) -> Self {
+        Self {
+            version: staged.update.version.clone(),
+            current_version: env!("CARGO_PKG_VERSION").to_string(),
+            body: staged.update.body.clone(),
+            channel: staged.channel,
+            size: staged.bytes.len() as u64,
+        }
+    }
+}
+
+pub struct UpdateState {
+    preferences: Mutex<UpdatePreferences>,
+    staged: Mutex<Option<StagedUpdate>>,
+    // Last version announced by the scheduler, so each is only announced once
+    notified_version: Mutex<Option<String>>,
+    cancel_download: Mutex<Option<Arc<Notify>>>,
+    // Set while an "install when idle" watcher is waiting
+    idle_install: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl Default for UpdateState {
+    fn default() -> Self {
+        Self {
+            preferences: Mutex::new(load_preferences()),
+            staged: Mutex::new(None),
+            notified_version: Mutex::new(None),
+            cancel_download: Mutex::new(None),
+            idle_install: Mutex::new(None),
+        }
+    }
+}
+
+// Update info structure for frontend
+#[derive(Clone, Serialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub body: Option<String>,
+    pub channel: UpdateChannel,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub percent: Option<f64>,
+    pub bytes_per_second: f64,
+    pub eta_seconds: Option<f64>,
+}
+
+// Tracks throughput with a smoothed rate and throttles progress events
+struct ProgressTracker {
+    started: Instant,
+    downloaded: u64,
+    sample_at: Instant,
+    sample_bytes: u64,
+    rate: f64,
+    last_emit: Option<Instant>,
+}
+
+impl ProgressTracker {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            downloaded: 0,
+            sample_at: now,
+            sample_bytes: 0,
+            rate: 0.0,
+            last_emit: None,
+        }
+    }
+
+    fn record(&mut self, chunk_length: usize, total: Option<u64>) -> Option<DownloadProgress> {
+        self.downloaded += chunk_length as u64;
+        let now = Instant::now();
+
+        let sample_elapsed = now.duration_since(self.sample_at);
+        if sample_elapsed >= SPEED_SAMPLE_INTERVAL {
+            let sample_rate = (self.downloaded - self.sample_bytes) as f64 / sample_elapsed.as_secs_f64();
+            self.rate = if self.rate == 0.0 { sample_rate } else { self.rate * 0.7 + sample_rate * 0.3 };
+            self.sample_at = now;
+            self.sample_bytes = self.downloaded;
+        }
+
+        let finished = total.is_some_and(|total| self.downloaded >= total);
+        let throttled = self
+            .last_emit
+            .is_some_and(|last| now.duration_since(last) < PROGRESS_EMIT_INTERVAL);
+        if throttled && !finished {
+            return None;
+        }
+        self.last_emit = Some(now);
+
+        // Before the first sample, fall back to the average since the start
+        let bytes_per_second = if self.rate > 0.0 {
+            self.rate
+        } else {
+            let elapsed = now.duration_since(self.started).as_secs_f64();
+            if elapsed > 0.0 { self.downloaded as f64 / elapsed } else { 0.0 }
+        };
+
+        Some(DownloadProgress {
+            downloaded: self.downloaded,
+            total,
+            percent: total
+                .filter(|total| *total > 0)
+                .map(|total| (self.downloaded as f64 / total as f64 * 100.0).min(100.0)),
+            bytes_per_second,
+            eta_seconds: total
+                .filter(|_| bytes_per_second > 0.0)
+                .map(|total| total.saturating_sub(self.downloaded) as f64 / bytes_per_second),
+        })
+    }
+}
+
+fn load_preferences() -> UpdatePreferences {
+    settings::get().updates
+}
+
+fn save_preferences(preferences: &UpdatePreferences) -> Result<(), String> {
+    settings::modify(|settings| settings.updates = preferences.clone()).map(|_| ())
+}
+
+pub fn apply_preferences(state: &UpdateState, preferences: UpdatePreferences) {
+    *state.preferences.lock().unwrap() = preferences;
+}
+
+// Serve a local manifest over loopback HTTP for a single request, so file://
+// endpoints can stand in for a release server while testing
+fn serve_local_manifest(path: PathBuf) -> Result<Url, String> {
+    if !cfg!(debug_assertions) {
+        return Err("file:// update endpoints are only supported in development builds".to_string());
+    }
+    let manifest = fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+    thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while Instant::now() < deadline {
+            match listener.accept() {
+                Ok((mut stream, _)) => {
+                    let _ = stream.set_nonblocking(false);
+                    let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+                    let mut request = [0u8; 4096];
+                    let _ = stream.read(&mut request);
+                    let header = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        manifest.len()
+                    );
+                    let _ = stream.write_all(header.as_bytes());
+                    let _ = stream.write_all(&manifest);
+                    return;
+                }
+                Err(_) => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    });
+
+    Url::parse(&format!("http://127.0.0.1:{}/latest.json", port)).map_err(|e| e.to_string())
+}
+
+fn resolve_endpoint(endpoint: &str) -> Result<Url, String> {
+    let url: Url = endpoint
+        .parse()
+        .map_err(|e| format!("Invalid update endpoint {:?}: {}", endpoint, e))?;
+    if url.scheme() == "file" {
+        let path = url
+            .to_file_path()
+            .map_err(|_| format!("Invalid file endpoint {:?}", endpoint))?;
+        return serve_local_manifest(path);
+    }
+    Ok(url)
+}
+
+// Build an updater for the selected channel, falling back to tauri.conf.json
+pub fn channel_updater(app: &AppHandle) -> Result<(Updater, UpdateChannel), String> {
+    let preferences = app.state::<UpdateState>().preferences.lock().unwrap().clone();
+    let channel = preferences.channel;
+
+    let mut builder = app.updater_builder();
+    if let Some(endpoint) = endpoint_for(&preferences, channel) {
+        let url = resolve_endpoint(&endpoint)?;
+        log::info!("Using {:?} update endpoint {}", channel, url);
+        builder = builder.endpoints(vec![url]).map_err(|e| e.to_string())?;
+    }
+
+    let updater = builder.build().map_err(|e| e.to_string())?;
+    Ok((updater, channel))
+}
+
+// Spread launch checks between 30s and 5min so a fleet doesn't hit GitHub at once
+fn launch_jitter() -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or(0);
+    Duration::from_secs(30 + u64::from(nanos) % 270)
+}
+
+async fn scheduled_check(app: &AppHandle) {
+    let (updater, channel) = match channel_updater(app) {
+        Ok(result) => result,
+        Err(err) => {
+            log::warn!("Scheduled update check skipped: {}", err);
+            return;
+        }
+    };
+
+    let update = match updater.check().await {
+        Ok(Some(update)) => update,
+        Ok(None) => {
+            log::info!("Scheduled update check: up to date");
+            return;
+        }
+        Err(err) => {
+            log::warn!("Scheduled update check failed: {}", err);
+            return;
+        }
+    };
+
+    let state = app.state::<UpdateState>();
+    if state.preferences.lock().unwrap().skipped_versions.contains(&update.version) {
+        log::info!("Update {} available but skipped by user", update.version);
+        return;
+    }
+    {
+        let mut notified = state.notified_version.lock().unwrap();
+        if notified.as_deref() == Some(update.version.as_str()) {
+            return;
+        }
+        *notified = Some(update.version.clone());
+    }
+
+    log::info!("Scheduled update check found {} on {:?} channel", update.version, channel);
+    let _ = app.emit("update-available", UpdateInfo {
+        version: update.version.clone(),
+        current_version: env!("CARGO_PKG_VERSION").to_string(),
+        body: update.body.clone(),
+        channel,
+    });
+}
+
+// Check on launch (with jitter) and then at the configured interval
+pub fn spawn_update_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(launch_jitter()).await;
+
+        loop {
+            let interval_hours = app
+                .state::<UpdateState>()
+                .preferences
+                .lock()
+                .unwrap()
+                .check_interval_hours;
+
+            if interval_hours > 0 {
+                scheduled_check(&app).await;
+            }
+
+            // Re-read the interval periodically so enabling checks takes effect
+            let interval = if interval_hours > 0 {
+                Duration::from_secs(interval_hours * 60 * 60).max(MIN_CHECK_INTERVAL)
+            } else {
+                MIN_CHECK_INTERVAL
+            };
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_update_channel(state: tauri::State<UpdateState>) -> UpdatePreferences {
+    state.preferences.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_update_channel(state: tauri::State<UpdateState>, channel: UpdateChannel) -> Result<(), String> {
+    let mut preferences = state.preferences.lock().unwrap();
+    preferences.channel = channel;
+    save_preferences(&preferences)?;
+    log::info!("Update channel set to {:?}", channel);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_update_endpoint(
+    state: tauri::State<UpdateState>,
+    channel: UpdateChannel,
+    endpoint: Option<String>,
+) -> Result<(), String> {
+    if let Some(endpoint) = &endpoint {
+        endpoint
+            .parse::<Url>()
+            .map_err(|e| format!("Invalid update endpoint {:?}: {}", endpoint, e))?;
+    }
+
+    let mut preferences = state.preferences.lock().unwrap();
+    match channel {
+        UpdateChannel::Stable => preferences.stable_endpoint = endpoint,
+        UpdateChannel::Beta => preferences.beta_endpoint = endpoint,
+    }
+    save_preferences(&preferences)
+}
+
+#[tauri::command]
+pub fn set_update_check_interval(state: tauri::State<UpdateState>, hours: u64) -> Result<(), String> {
+    let mut preferences = state.preferences.lock().unwrap();
+    preferences.check_interval_hours = hours;
+    save_preferences(&preferences)
+}
+
+// Dismiss a version so background checks stop announcing it
+#[tauri::command]
+pub fn skip_update_version(state: tauri::State<UpdateState>, version: String) -> Result<(), String> {
+    let mut preferences = state.preferences.lock().unwrap();
+    if !preferences.skipped_versions.contains(&version) {
+        preferences.skipped_versions.push(version);
+    }
+    save_preferences(&preferences)
+}
+
+#[tauri::command]
+pub fn clear_skipped_updates(state: tauri::State<UpdateState>) -> Result<(), String> {
+    let mut preferences = state.preferences.lock().unwrap();
+    preferences.skipped_versions.clear();
+    save_preferences(&preferences)
+}
+
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let (updater, channel) = channel_updater(&app)?;
+
+    match updater.check().await {
+        Ok(Some(update)) => {
+            log::info!("Update available on {:?} channel: {}", channel, update.version);
+            Ok(Some(UpdateInfo {
+                version: update.version.clone(),
+                current_version: env!("CARGO_PKG_VERSION").to_string(),
+                body: update.body.clone(),
+                channel,
+            }))
+        }
+        Ok(None) => {
+            log::info!("No update available");
+            Ok(None)
+        }
+        Err(e) => {
+            log::error!("Failed to check for updates: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+fn emit_update_error(app: &AppHandle, message: &str) {
+    log::error!("Update failed: {}", message);
+    let _ = app.emit("update-error", message.to_string());
+}
+
+async fn download_and_stage(app: &AppHandle) -> Result<StagedUpdateInfo, String> {
+    let (updater, channel) = channel_updater(app).inspect_err(|e| emit_update_error(app, e))?;
+
+    let update = match updater.check().await {
+        Ok(Some(update)) => update,
+        Ok(None) => return Err("No update available".to_string()),
+        Err(e) => {
+            emit_update_error(app, &e.to_string());
+            return Err(e.to_string());
+        }
+    };
+
+    log::info!("Downloading update: {}", update.version);
+
+    let state = app.state::<UpdateState>();
+    let cancel = Arc::new(Notify::new());
+    *state.cancel_download.lock().unwrap() = Some(Arc::clone(&cancel));
+
+    // Emit download progress events
+    let app_handle = app.clone();
+    let mut tracker = ProgressTracker::new();
+
+    let result = tokio::select! {
+        result = update.download(
+            |chunk_length, content_length| {
+                if let Some(progress) = tracker.record(chunk_length, content_length) {
+                    let _ = app_handle.emit("update-download-progress", progress);
+                }
+            },
+            || {
+                log::info!("Download finished, update staged");
+            },
+        ) => Some(result),
+        _ = cancel.notified() => None,
+    };
+    *state.cancel_download.lock().unwrap() = None;
+
+    let bytes = match result {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => {
+            emit_update_error(app, &e.to_string());
+            return Err(e.to_string());
+        }
+        None => {
+            log::info!("Update download cancelled");
+            let _ = app.emit("update-cancelled", update.version.clone());
+            return Err("Update download cancelled".to_string());
+        }
+    };
+
+    let staged = StagedUpdate { update, bytes, channel };
+    let info = StagedUpdateInfo::from(&staged);
+    *state.staged.lock().unwrap() = Some(staged);
+
+    let _ = app.emit("update-staged", info.clone());
+    Ok(info)
+}
+
+// Bring the server and tunnel back after a failed install
+// Restart only what install_staged stopped, so a failed update never brings
+// up a tunnel the user had stopped or tunnel.startup keeps off
+fn restore_processes(app: &AppHandle, server: bool, tunnel: bool) {
+    let app = app.clone();
+    thread::spawn(move || {
+        let state = app.state::<AppState>();
+        if server {
+            if let Err(err) = start_server_internal(&app, &state) {
+                log::error!("Failed to restart server after update error: {}", err);
+                return;
+            }
+        }
+        if tunnel && wait_for_server_health(10, 500) {
+            if let Err(err) = start_tunnel_internal(&app, &state) {
+                log::error!("Failed to restart tunnel after update error: {}", err);
+            }
+        }
+    });
+}
+
+fn install_staged(app: &AppHandle, restart: bool) -> Result<(), String> {
+    let staged = app
+        .state::<UpdateState>()
+        .staged
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| "No staged update".to_string())?;
+
+    // Stop child processes cleanly before the bundle is replaced
+    let state = app.state::<AppState>();
+    let server_was_running = state.server_process.lock().unwrap().is_some();
+    let tunnel_was_running = state.tunnel_process.lock().unwrap().is_some();
+    stop_tunnel_internal(&state);
+    stop_server_internal(&state);
+
+    log::info!("Installing staged update {}", staged.update.version);
+    let _ = app.emit("update-installing", true);
+    if let Err(err) = staged.update.install(&staged.bytes) {
+        emit_update_error(app, &err.to_string());
+        restore_processes(app, server_was_running, tunnel_was_running);
+        return Err(err.to_string());
+    }
+
+    if restart {
+        log::info!("Update installed, restarting...");
+        app.restart();
+    }
+    log::info!("Update installed; it will be used on next launch");
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerActivity {
+    sessions: u64,
+    last_activity: Option<u64>,
+    now: u64,
+}
+
+#[derive(Clone, Serialize)]
+struct IdleCountdown {
+    version: String,
+    active_sessions: u64,
+    idle_seconds: u64,
+    required_idle_seconds: u64,
+    seconds_remaining: u64,
+}
+
+// Returns (active sessions, seconds since the last PTY I/O). If the server
+// can't be reached no PTY can be running, so that counts as idle.
+fn idle_status(required: u64) -> (u64, u64) {
+    let activity = server_get("/api/activity")
+        .and_then(|body| serde_json::from_str::<ServerActivity>(&body).ok());
+    match activity {
+        Some(activity) if activity.sessions > 0 => {
+            let idle_ms = activity
+                .last_activity
+                .map(|last| activity.now.saturating_sub(last))
+                .unwrap_or(u64::MAX);
+            (activity.sessions, idle_ms / 1000)
+        }
+        _ => (0, required),
+    }
+}
+
+fn spawn_idle_install(app: AppHandle, stop: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let state = app.state::<UpdateState>();
+        while !stop.load(Ordering::Relaxed) {
+            let Some(version) = state
+                .staged
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|staged| staged.update.version.clone())
+            else {
+                log::info!("Idle install stopped: no staged update");
+                break;
+            };
+
+            let required = state.preferences.lock().unwrap().idle_install_seconds;
+            let (active_sessions, idle_seconds) = idle_status(required);
+
+            if active_sessions == 0 || idle_seconds >= required {
+                log::info!("Terminals idle for {}s; installing update {}", idle_seconds, version);
+                *state.idle_install.lock().unwrap() = None;
+                if let Err(err) = install_staged(&app, true) {
+                    log::error!("Idle install failed: {}", err);
+                }
+                return;
+            }
+
+            let _ = app.emit("update-idle-countdown", IdleCountdown {
+                version,
+                active_sessions,
+                idle_seconds,
+                required_idle_seconds: required,
+                seconds_remaining: required - idle_seconds,
+            });
+            thread::sleep(IDLE_POLL_INTERVAL);
+        }
+        *state.idle_install.lock().unwrap() = None;
+    });
+}
+
+// Called from the exit handler once child processes have been stopped
+pub fn install_staged_on_exit(app: &AppHandle) {
+    let Some(staged) = app.state::<UpdateState>().staged.lock().unwrap().take() else {
+        return;
+    };
+
+    log::info!("Installing staged update {} before exit", staged.update.version);
+    if let Err(err) = staged.update.install(&staged.bytes) {
+        log::error!("Failed to install staged update on exit: {}", err);
+    }
+}
+
+#[tauri::command]
+pub async fn download_update(app: AppHandle) -> Result<StagedUpdateInfo, String> {
+    download_and_stage(&app).await
+}
+
+#[tauri::command]
+pub fn cancel_update_download(state: tauri::State<UpdateState>) -> bool {
+    match state.cancel_download.lock().unwrap().as_ref() {
+        Some(cancel) => {
+            cancel.notify_one();
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub fn get_staged_update(state: tauri::State<UpdateState>) -> Option<StagedUpdateInfo> {
+    state.staged.lock().unwrap().as_ref().map(StagedUpdateInfo::from)
+}
+
+#[tauri::command]
+pub fn install_staged_update(app: AppHandle, restart: Option<bool>) -> Result<(), String> {
+    install_staged(&app, restart.unwrap_or(true))
+}
+
+// Download if needed, then install once every terminal has been idle long enough
+#[tauri::command]
+pub async fn install_update_when_idle(app: AppHandle) -> Result<(), String> {
+    let has_staged = app.state::<UpdateState>().staged.lock().unwrap().is_some();
+    if !has_staged {
+        download_and_stage(&app).await?;
+    }
+
+    let state = app.state::<UpdateState>();
+    let mut idle_install = state.idle_install.lock().unwrap();
+    if idle_install.is_some() {
+        return Ok(());
+    }
+    let stop = Arc::new(AtomicBool::new(false));
+    *idle_install = Some(Arc::clone(&stop));
+    drop(idle_install);
+
+    log::info!("Update will install once terminals are idle");
+    spawn_idle_install(app.clone(), stop);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn cancel_idle_install(state: tauri::State<UpdateState>) -> bool {
+    match state.idle_install.lock().unwrap().take() {
+        Some(stop) => {
+            stop.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub fn set_update_idle_seconds(state: tauri::State<UpdateState>, seconds: u64) -> Result<(), String> {
+    let mut preferences = state.preferences.lock().unwrap();
+    preferences.idle_install_seconds = seconds;
+    save_preferences(&preferences)
+}
+
+// Kept for older callers. Restarting here would kill live terminal sessions,
+// so it stages the update and installs it once terminals are idle (or at exit).
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    install_update_when_idle(app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_percent_for_a_known_size() {
+        let mut tracker = ProgressTracker::new();
+        let progress = tracker.record(50, Some(200)).unwrap();
+        assert_eq!(progress.downloaded, 50);
+        assert_eq!(progress.total, Some(200));
+        assert_eq!(progress.percent, Some(25.0));
+    }
+
+    #[test]
+    fn throttles_events_but_always_reports_completion() {
+        let mut tracker = ProgressTracker::new();
+        assert!(tracker.record(10, Some(100)).is_some());
+        assert!(tracker.record(10, Some(100)).is_none());
+
+        let done = tracker.record(80, Some(100)).unwrap();
+        assert_eq!(done.downloaded, 100);
+        assert_eq!(done.percent, Some(100.0));
+        assert_eq!(done.eta_seconds.unwrap_or(0.0), 0.0);
+    }
+
+    #[test]
+    fn leaves_percent_and_eta_empty_for_an_unknown_size() {
+        let mut tracker = ProgressTracker::new();
+        let progress = tracker.record(4096, None).unwrap();
+        assert_eq!(progress.downloaded, 4096);
+        assert_eq!(progress.total, None);
+        assert_eq!(progress.percent, None);
+        assert_eq!(progress.eta_seconds, None);
+    }
+
+    #[test]
+    fn empty_total_has_no_percent() {
+        let mut tracker = ProgressTracker::new();
+        assert_eq!(tracker.record(0, Some(0)).unwrap().percent, None);
+    }
+}
@@ -418,6 +418,7 @@ function AppContent() {
     updateInfo,
     isDownloading,
     downloadProgress,
+    isStaged,
     installUpdate,
     dismissUpdate,
   } = useUpdater();
@@ -467,6 +468,7 @@ function AppContent() {
             updateInfo={updateInfo}
             isDownloading={isDownloading}
             downloadProgress={downloadProgress}
+            isStaged={isStaged}
             onInstall={installUpdate}
             onDismiss={dismissUpdate}
           />
@@ -51,7 +51,7 @@ interface TunnelStatusProps {
 }
 
 const TunnelStatus: React.FC<TunnelStatusProps> = ({ isTunnelAccess = false }) => {
-  const { url, isConnected, isLoading, startTunnel, stopTunnel, refreshTunnel, copyUrl } = useTunnel();
+  const { url, shareUrl, isConnected, isLoading, startTunnel, stopTunnel, refreshTunnel, copyUrl } = useTunnel();
   const [copied, setCopied] = useState(false);
   const [showQR, setShowQR] = useState(false);
   const [showStopModal, setShowStopModal] = useState(false);
@@ -175,7 +175,7 @@ const TunnelStatus: React.FC<TunnelStatusProps> = ({ isTunnelAccess = false }) =
             </button>
             <div className="qr-modal-content">
               <QRCodeSVG
-                value={url}
+                value={shareUrl ?? url}
                 size={200}
                 bgColor="#ffffff"
                 fgColor="#000000"
@@ -1,21 +1,49 @@
 import React from 'react';
-import { UpdateInfo } from '../hooks/useUpdater';
+import { DownloadProgress, UpdateInfo } from '../hooks/useUpdater';
 
 interface UpdateModalProps {
   updateInfo: UpdateInfo;
   isDownloading: boolean;
-  downloadProgress: number;
+  downloadProgress: DownloadProgress | null;
+  isStaged: boolean;
   onInstall: () => void;
   onDismiss: () => void;
 }
 
+function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+function formatEta(seconds: number): string {
+  if (seconds < 60) return `${Math.ceil(seconds)}s left`;
+  return `${Math.floor(seconds / 60)}m ${Math.ceil(seconds % 60)}s left`;
+}
+
+// "12.5 MB of 40.0 MB · 2.1 MB/s · 13s left", dropping what isn't known yet
+function describeProgress(progress: DownloadProgress | null): string {
+  if (!progress) return 'Starting download...';
+  const parts = [
+    progress.total !== null
+      ? `${formatBytes(progress.downloaded)} of ${formatBytes(progress.total)}`
+      : `${formatBytes(progress.downloaded)} downloaded`,
+  ];
+  if (progress.bytes_per_second > 0) parts.push(`${formatBytes(progress.bytes_per_second)}/s`);
+  if (progress.eta_seconds !== null) parts.push(formatEta(progress.eta_seconds));
+  return parts.join(' · ');
+}
+
 const UpdateModal: React.FC<UpdateModalProps> = ({
   updateInfo,
   isDownloading,
   downloadProgress,
+  isStaged,
   onInstall,
   onDismiss,
 }) => {
+  const percent = downloadProgress?.percent ?? null;
+
   return (
     <div className="update-modal-overlay">
       <div className="update-modal">
@@ -51,23 +79,44 @@ const UpdateModal: React.FC<UpdateModalProps> = ({
             </div>
           )}
 
+          {isStaged && (
+            <div className="update-progress">
+              <span className="update-progress-text">
+                Update downloaded. It will install once your terminals have been idle for a while, or when you quit.
+              </span>
+            </div>
+          )}
+
           {isDownloading && (
             <div className="update-progress">
               <div className="update-progress-bar">
-                <div
-                  className="update-progress-fill"
-                  style={{ width: `${downloadProgress}%` }}
-                />
+                {percent !== null ? (
+                  <div
+                    className="update-progress-fill"
+                    style={{ width: `${Math.round(percent)}%` }}
+                  />
+                ) : (
+                  <div className="update-progress-fill update-progress-indeterminate" />
+                )}
               </div>
               <span className="update-progress-text">
-                {downloadProgress < 100 ? `Downloading... ${downloadProgress}%` : 'Installing...'}
+                {percent !== null && percent >= 100
+                  ? 'Verifying download...'
+                  : `${percent !== null ? `${Math.round(percent)}% · ` : ''}${describeProgress(downloadProgress)}`}
               </span>
             </div>
           )}
         </div>
 
         <div className="update-modal-actions">
-          {!isDownloading ? (
+          {isStaged ? (
+            <button
+              className="update-btn update-btn-secondary"
+              onClick={onDismiss}
+            >
+              Close
+            </button>
+          ) : !isDownloading ? (
             <>
               <button
                 className="update-btn update-btn-secondary"
@@ -79,7 +128,7 @@ const UpdateModal: React.FC<UpdateModalProps> = ({
                 className="update-btn update-btn-primary"
                 onClick={onInstall}
               >
-                Update Now
+                Download Update
               </button>
             </>
           ) : (
@@ -18,6 +18,8 @@ export function useTunnel() {
     isLoading: true,
     error: null,
   });
+  // Tunnel URL plus the session access token, which is what other devices need
+  const [shareUrl, setShareUrl] = useState<string | null>(null);
   const cleanupRef = useRef<(() => void) | null>(null);
   const pollRef = useRef<ReturnType<typeof setInterval> | null>(null);
   const pollingRef = useRef(false);
@@ -155,10 +157,35 @@ export function useTunnel() {
     }
   }, [isDesktopApp, isTunnelAccess]);
 
+  // The token rotates with the tunnel, so refetch whenever the URL changes
+  useEffect(() => {
+    if (!isDesktopApp || !state.url) {
+      setShareUrl(null);
+      return;
+    }
+    let cancelled = false;
+    import('@tauri-apps/api/core')
+      .then(({ invoke }) => invoke<string | null>('get_share_url'))
+      .then((url) => {
+        if (!cancelled) setShareUrl(url);
+      })
+      .catch((e) => console.error('[useTunnel] Failed to get share URL:', e));
+    return () => {
+      cancelled = true;
+    };
+  }, [isDesktopApp, state.url]);
+
   const copyUrl = useCallback(async () => {
-    if (state.url) {
+    const url = shareUrl ?? state.url;
+    if (url) {
       try {
-        await navigator.clipboard.writeText(state.url);
+        if (isDesktopApp) {
+          // Native clipboard write; works even when the webview lacks focus
+          const { invoke } = await import('@tauri-apps/api/core');
+          await invoke('copy_tunnel_url');
+        } else {
+          await navigator.clipboard.writeText(url);
+        }
         return true;
       } catch (e) {
         console.error('Failed to copy URL:', e);
@@ -166,7 +193,7 @@ export function useTunnel() {
       }
     }
     return false;
-  }, [state.url]);
+  }, [isDesktopApp, shareUrl, state.url]);
 
   const startTunnel = useCallback(async () => {
     if (!isDesktopApp) return;
@@ -225,6 +252,7 @@ export function useTunnel() {
 
   return {
     url: state.url,
+    shareUrl: shareUrl ?? state.url,
     isConnected: state.isConnected,
     isLoading: state.isLoading,
     error: state.error,
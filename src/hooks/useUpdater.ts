@@ -6,12 +6,24 @@ export interface UpdateInfo {
   version: string;
   current_version: string;
   body: string | null;
+  channel: 'stable' | 'beta';
+}
+
+export interface DownloadProgress {
+  downloaded: number;
+  total: number | null;
+  percent: number | null;
+  bytes_per_second: number;
+  eta_seconds: number | null;
 }
 
 interface UpdaterState {
   isChecking: boolean;
   isDownloading: boolean;
-  downloadProgress: number;
+  // Null until the first progress event arrives
+  downloadProgress: DownloadProgress | null;
+  // Downloaded and waiting for terminals to go idle (or for the app to quit)
+  isStaged: boolean;
   updateAvailable: boolean;
   updateInfo: UpdateInfo | null;
   error: string | null;
@@ -33,7 +45,8 @@ export function useUpdater() {
   const [state, setState] = useState<UpdaterState>({
     isChecking: false,
     isDownloading: false,
-    downloadProgress: 0,
+    downloadProgress: null,
+    isStaged: false,
     updateAvailable: false,
     updateInfo: null,
     error: null,
@@ -95,16 +108,17 @@ export function useUpdater() {
     }
   }, [isDesktopApp, isLicensed]);
 
-  // Install update
+  // Download now, install once terminals are idle so running sessions survive
   const installUpdate = useCallback(async () => {
     if (!isDesktopApp || !state.updateAvailable) return;
 
-    setState(prev => ({ ...prev, isDownloading: true, downloadProgress: 0 }));
+    setState(prev => ({ ...prev, isDownloading: true, downloadProgress: null }));
 
     try {
       const { invoke } = await import('@tauri-apps/api/core');
-      await invoke('install_update');
-      // App will restart after install
+      await invoke('install_update_when_idle');
+      // The app restarts by itself once terminals are idle, or installs on quit
+      setState(prev => ({ ...prev, isDownloading: false, isStaged: true }));
     } catch (err) {
       const errorMessage = err instanceof Error ? err.message : String(err);
       setState(prev => ({
@@ -144,7 +158,8 @@ export function useUpdater() {
       try {
         const { listen } = await import('@tauri-apps/api/event');
 
-        unlisten = await listen<number>('update-download-progress', (event) => {
+        unlisten = await listen<DownloadProgress>('update-download-progress', (event) => {
+          // percent and total are null when the server sends no Content-Length
           setState(prev => ({ ...prev, downloadProgress: event.payload }));
         });
       } catch (err) {